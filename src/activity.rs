@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tokio::sync::broadcast;
+
+use crate::win::WindowMetadata;
+
+// How many events a slow subscriber may fall behind before missing some.
+const CAPACITY: usize = 256;
+
+// What the app does, streamed to control socket subscribers and recorded in
+// the history.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Activity {
+    // A focused window rule checked the window against its patterns.
+    RuleEvaluated {
+        rule: String,
+        window: WindowMetadata,
+        matched: bool,
+    },
+    // A rule sent reports to its devices.
+    RuleSent {
+        rule: String,
+        event: String,
+        devices: Vec<String>,
+        reports: usize,
+    },
+    // Writing a report to one of the rule's devices failed.
+    ReportFailed {
+        rule: String,
+        device: String,
+        error: String,
+    },
+    FocusedWindowChanged {
+        window: WindowMetadata,
+    },
+    // An event source failed, e.g. a listener couldn't start.
+    Error {
+        source: String,
+        error: String,
+    },
+}
+
+impl std::fmt::Display for Activity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let title = |window: &WindowMetadata| window.title.clone().unwrap_or_default();
+        match self {
+            Self::RuleEvaluated {
+                rule,
+                window,
+                matched,
+            } => write!(
+                f,
+                "{rule} {} \"{}\"",
+                if *matched { "matched" } else { "did not match" },
+                title(window)
+            ),
+            Self::RuleSent {
+                rule,
+                event,
+                devices,
+                reports,
+            } => write!(
+                f,
+                "{rule} ({event}) sent {reports} report(s) to {}",
+                devices.join(", ")
+            ),
+            Self::ReportFailed {
+                rule,
+                device,
+                error,
+            } => write!(f, "{rule} failed to write to {device}: {error}"),
+            Self::FocusedWindowChanged { window } => write!(f, "Focused \"{}\"", title(window)),
+            Self::Error { source, error } => write!(f, "{source}: {error}"),
+        }
+    }
+}
+
+impl Activity {
+    pub fn rule(&self) -> Option<&str> {
+        match self {
+            Self::RuleEvaluated { rule, .. }
+            | Self::RuleSent { rule, .. }
+            | Self::ReportFailed { rule, .. } => Some(rule),
+            Self::FocusedWindowChanged { .. } | Self::Error { .. } => None,
+        }
+    }
+
+    pub fn involves_device(&self, name: &str) -> bool {
+        match self {
+            Self::RuleSent { devices, .. } => devices.iter().any(|device| device == name),
+            Self::ReportFailed { device, .. } => device == name,
+            _ => false,
+        }
+    }
+}
+
+pub static ACTIVITY_TX: LazyLock<broadcast::Sender<Activity>> = LazyLock::new(|| {
+    let (tx, _rx) = broadcast::channel(CAPACITY);
+    tx
+});
+
+pub fn publish(activity: Activity) {
+    // Nobody listening is fine.
+    let _ = ACTIVITY_TX.send(activity);
+}
+
+// Prints the error and publishes it, so it shows up in the history too.
+pub fn error(source: &str, error: impl std::fmt::Display) {
+    tracing::error!("{}: {}", source, error);
+    publish(Activity::Error {
+        source: source.to_string(),
+        error: error.to_string(),
+    });
+}
@@ -0,0 +1,400 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CaptureState {
+    pub microphone: bool,
+    pub camera: bool,
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CaptureDevice {
+    #[default]
+    Any,
+    Microphone,
+    Camera,
+}
+
+impl CaptureState {
+    pub fn in_use(&self, device: CaptureDevice) -> bool {
+        match device {
+            CaptureDevice::Any => self.microphone || self.camera,
+            CaptureDevice::Microphone => self.microphone,
+            CaptureDevice::Camera => self.camera,
+        }
+    }
+}
+
+// What each rule last saw, so its reports are only sent when that changes.
+#[derive(Debug, Default)]
+pub struct CaptureChanges {
+    in_use: HashMap<String, bool>,
+}
+
+impl CaptureChanges {
+    // Whether the rule's device is in use, if that changed since the rule was
+    // last updated. The first update always counts as a change.
+    pub fn update(
+        &mut self,
+        rule: &str,
+        device: CaptureDevice,
+        state: &CaptureState,
+    ) -> Option<bool> {
+        let in_use = state.in_use(device);
+        (self.in_use.insert(rule.to_string(), in_use) != Some(in_use)).then_some(in_use)
+    }
+}
+
+// Where capture state comes from, so it can be faked. Blocking, the system
+// provider scans processes and may spawn `pactl`.
+pub trait CaptureProvider: Send {
+    fn state(&mut self) -> CaptureState;
+}
+
+#[cfg(target_os = "linux")]
+pub struct SystemCaptureProvider {
+    proc_root: std::path::PathBuf,
+}
+
+#[cfg(target_os = "linux")]
+pub fn system_provider() -> Box<dyn CaptureProvider> {
+    Box::new(SystemCaptureProvider {
+        proc_root: "/proc".into(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+impl CaptureProvider for SystemCaptureProvider {
+    fn state(&mut self) -> CaptureState {
+        let mut state = CaptureState::default();
+
+        let Ok(processes) = std::fs::read_dir(&self.proc_root) else {
+            return state;
+        };
+
+        // Open webcams, and ALSA capture devices used without a sound server.
+        for process in processes.flatten() {
+            if !process
+                .file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+            {
+                continue;
+            }
+            let Ok(fds) = std::fs::read_dir(process.path().join("fd")) else {
+                continue;
+            };
+            for fd in fds.flatten() {
+                let Ok(target) = std::fs::read_link(fd.path()) else {
+                    continue;
+                };
+                let target = target.to_string_lossy();
+                if target.starts_with("/dev/video") {
+                    state.camera = true;
+                } else if target.starts_with("/dev/snd/pcm") && target.ends_with('c') {
+                    state.microphone = true;
+                }
+            }
+            if state.camera && state.microphone {
+                return state;
+            }
+        }
+
+        state.microphone = state.microphone || pulse_source_outputs_active();
+        state
+    }
+}
+
+// Recording streams of PulseAudio or PipeWire's pulse server, other than
+// those recording a sink's monitor, e.g. audio visualisers.
+#[cfg(target_os = "linux")]
+fn pulse_source_outputs_active() -> bool {
+    let pactl = |args: &[&str]| {
+        std::process::Command::new("pactl")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    let Some(outputs) = pactl(&["list", "short", "source-outputs"]) else {
+        return false;
+    };
+    // id, owner module, client, source, ...
+    let sources_in_use: Vec<&str> = outputs
+        .lines()
+        .filter_map(|line| line.split('\t').nth(3))
+        .collect();
+    if sources_in_use.is_empty() {
+        return false;
+    }
+
+    let monitors: Vec<String> = pactl(&["list", "short", "sources"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let index = fields.next()?;
+            let name = fields.next()?;
+            name.ends_with(".monitor").then(|| index.to_string())
+        })
+        .collect();
+
+    sources_in_use
+        .iter()
+        .any(|source| !monitors.iter().any(|monitor| monitor == source))
+}
+
+// Windows records which apps use the microphone and webcam in the capability
+// access manager, an app still using one has no stop time yet.
+#[cfg(windows)]
+pub struct SystemCaptureProvider;
+
+#[cfg(windows)]
+pub fn system_provider() -> Box<dyn CaptureProvider> {
+    Box::new(SystemCaptureProvider)
+}
+
+#[cfg(windows)]
+impl CaptureProvider for SystemCaptureProvider {
+    fn state(&mut self) -> CaptureState {
+        const CONSENT_STORE: &str =
+            r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+
+        CaptureState {
+            microphone: registry::any_app_in_use(&format!(r"{CONSENT_STORE}\microphone")),
+            camera: registry::any_app_in_use(&format!(r"{CONSENT_STORE}\webcam")),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod registry {
+    use windows::{
+        Win32::{
+            Foundation::ERROR_SUCCESS,
+            System::Registry::{
+                HKEY, HKEY_CURRENT_USER, KEY_READ, RRF_RT_REG_QWORD, RegCloseKey, RegEnumKeyExW,
+                RegGetValueW, RegOpenKeyExW,
+            },
+        },
+        core::{PCWSTR, PWSTR},
+    };
+
+    struct KeyGuard(HKEY);
+    impl Drop for KeyGuard {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = RegCloseKey(self.0);
+            }
+        }
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    fn open(parent: HKEY, path: &str) -> Option<KeyGuard> {
+        let path = wide(path);
+        let mut key = HKEY::default();
+        let result =
+            unsafe { RegOpenKeyExW(parent, PCWSTR(path.as_ptr()), None, KEY_READ, &mut key) };
+        (result == ERROR_SUCCESS).then_some(KeyGuard(key))
+    }
+
+    fn subkeys(key: &KeyGuard) -> Vec<String> {
+        let mut names = Vec::new();
+        for index in 0.. {
+            let mut buf = [0u16; 512];
+            let mut len = buf.len() as u32;
+            let result = unsafe {
+                RegEnumKeyExW(
+                    key.0,
+                    index,
+                    Some(PWSTR(buf.as_mut_ptr())),
+                    &mut len,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+            };
+            if result != ERROR_SUCCESS {
+                break;
+            }
+            names.push(String::from_utf16_lossy(&buf[..len as usize]));
+        }
+        names
+    }
+
+    fn qword(key: &KeyGuard, subkey: &str, value: &str) -> Option<u64> {
+        let subkey = wide(subkey);
+        let value = wide(value);
+        let mut data = 0u64;
+        let mut size = std::mem::size_of::<u64>() as u32;
+        let result = unsafe {
+            RegGetValueW(
+                key.0,
+                PCWSTR(subkey.as_ptr()),
+                PCWSTR(value.as_ptr()),
+                RRF_RT_REG_QWORD,
+                None,
+                Some(&mut data as *mut u64 as *mut _),
+                Some(&mut size),
+            )
+        };
+        (result == ERROR_SUCCESS).then_some(data)
+    }
+
+    fn any_in_use(key: &KeyGuard) -> bool {
+        subkeys(key).iter().any(|app| {
+            if app == "NonPackaged" {
+                return open(key.0, app).is_some_and(|non_packaged| any_in_use(&non_packaged));
+            }
+            qword(key, app, "LastUsedTimeStart").unwrap_or_default() > 0
+                && qword(key, app, "LastUsedTimeStop") == Some(0)
+        })
+    }
+
+    pub fn any_app_in_use(path: &str) -> bool {
+        open(HKEY_CURRENT_USER, path).is_some_and(|key| any_in_use(&key))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub struct SystemCaptureProvider;
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn system_provider() -> Box<dyn CaptureProvider> {
+    Box::new(SystemCaptureProvider)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+impl CaptureProvider for SystemCaptureProvider {
+    fn state(&mut self) -> CaptureState {
+        CaptureState::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Replays a list of states, then stays idle.
+    struct FakeProvider(std::vec::IntoIter<CaptureState>);
+
+    impl CaptureProvider for FakeProvider {
+        fn state(&mut self) -> CaptureState {
+            self.0.next().unwrap_or_default()
+        }
+    }
+
+    const MIC: CaptureState = CaptureState {
+        microphone: true,
+        camera: false,
+    };
+    const BOTH: CaptureState = CaptureState {
+        microphone: true,
+        camera: true,
+    };
+
+    #[test]
+    fn in_use_per_device() {
+        assert!(MIC.in_use(CaptureDevice::Any));
+        assert!(MIC.in_use(CaptureDevice::Microphone));
+        assert!(!MIC.in_use(CaptureDevice::Camera));
+        assert!(!CaptureState::default().in_use(CaptureDevice::Any));
+    }
+
+    #[test]
+    fn reports_only_changes() {
+        let mut provider: Box<dyn CaptureProvider> = Box::new(FakeProvider(
+            vec![CaptureState::default(), MIC, BOTH, BOTH, MIC].into_iter(),
+        ));
+        let mut changes = CaptureChanges::default();
+
+        let mut mic = Vec::new();
+        let mut camera = Vec::new();
+        for _ in 0..6 {
+            let state = provider.state();
+            mic.push(changes.update("mic", CaptureDevice::Microphone, &state));
+            camera.push(changes.update("camera", CaptureDevice::Camera, &state));
+        }
+
+        assert_eq!(
+            mic,
+            [Some(false), Some(true), None, None, None, Some(false)]
+        );
+        assert_eq!(
+            camera,
+            [Some(false), None, Some(true), None, Some(false), None]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    fn fake_proc(name: &str, fds: &[(&str, &str)]) -> std::path::PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("locked-in-capture-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&root);
+        for (i, (process, target)) in fds.iter().enumerate() {
+            let fd_dir = root.join(process).join("fd");
+            std::fs::create_dir_all(&fd_dir).unwrap();
+            std::os::unix::fs::symlink(target, fd_dir.join(i.to_string())).unwrap();
+        }
+        root
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_open_devices_from_proc() {
+        let proc_root = fake_proc(
+            "both",
+            &[
+                ("100", "/dev/null"),
+                ("100", "/dev/video0"),
+                ("200", "/dev/snd/pcmC0D0c"),
+            ],
+        );
+        let state = SystemCaptureProvider {
+            proc_root: proc_root.clone(),
+        }
+        .state();
+        std::fs::remove_dir_all(proc_root).unwrap();
+        assert_eq!(state, BOTH);
+    }
+
+    // The microphone is found in /proc, so pactl isn't asked.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn skips_playback_and_non_processes() {
+        let proc_root = fake_proc(
+            "playback",
+            &[
+                ("100", "/dev/snd/pcmC0D0p"),
+                ("200", "/dev/snd/pcmC1D0c"),
+                ("self", "/dev/video0"),
+            ],
+        );
+        let state = SystemCaptureProvider {
+            proc_root: proc_root.clone(),
+        }
+        .state();
+        std::fs::remove_dir_all(proc_root).unwrap();
+        assert_eq!(state, MIC);
+    }
+}
@@ -0,0 +1,235 @@
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    config::{Config, Rule, RuleSummary},
+    hid::{HID_DEVICES, HidMetadata},
+    win,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "locked-in",
+    version,
+    about = "Sends reports to raw HID peripherals when system events happen"
+)]
+pub struct Cli {
+    #[arg(long, help = "Evaluate rules without any window or tray icon")]
+    pub headless: bool,
+    #[arg(long, global = true, help = "Print JSON instead of text")]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    #[command(subcommand, about = "Connected HID devices")]
+    Devices(DevicesCommand),
+    #[command(about = "Send raw bytes to a device from the config's registry")]
+    Send {
+        #[arg(long, help = "The device's name in config.toml")]
+        device: String,
+        #[arg(long, help = "The report as hex bytes, e.g. \"87 01\"")]
+        hex: String,
+    },
+    #[command(subcommand, about = "Rules in config.toml")]
+    Rules(RulesCommand),
+    #[command(subcommand, about = "config.toml itself")]
+    Config(ConfigCommand),
+    #[command(subcommand, about = "Windows as rules see them")]
+    Window(WindowCommand),
+}
+
+#[derive(Subcommand)]
+pub enum DevicesCommand {
+    #[command(about = "List connected HID devices with their usages")]
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum RulesCommand {
+    #[command(about = "List rules with their event and devices")]
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    #[command(about = "Check config.toml for errors, exits with 1 if there are any")]
+    Validate,
+}
+
+#[derive(Subcommand)]
+pub enum WindowCommand {
+    #[command(about = "Print the focused window")]
+    Current,
+}
+
+#[derive(Serialize)]
+struct Validation {
+    valid: bool,
+    problems: Vec<String>,
+}
+
+// Unlike the app, the command line neither creates a missing config nor
+// migrates an old one, it only reads.
+fn load_config() -> Result<(Config, bool)> {
+    let path = Config::path()?;
+    anyhow::ensure!(path.is_file(), "{} does not exist", path.display());
+    Config::read()
+}
+
+fn print_json(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn devices_list(json: bool) -> Result<()> {
+    let mut devices: Vec<HidMetadata> = HID_DEVICES
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Failed to acquire HID_DEVICES lock"))?
+        .get_metadata_list();
+    devices.sort_by_key(|device| (device.vendor_id, device.product_id));
+
+    if json {
+        return print_json(&devices);
+    }
+    for device in devices {
+        println!(
+            "{:04x}:{:04x} {} {}",
+            device.vendor_id, device.product_id, device.manufacturer_string, device.product_string
+        );
+        for interface in device.interfaces {
+            print!(
+                "  usage_page 0x{:04x} usage 0x{:04x} interface {}",
+                interface.usage.usage_page, interface.usage.usage, interface.interface_number
+            );
+            if let Some(serial_number) = interface.serial_number {
+                print!(" serial {serial_number}");
+            }
+            println!(" {}", interface.path);
+        }
+    }
+    Ok(())
+}
+
+fn send(device_name: &str, hex_bytes: &str, json: bool) -> Result<()> {
+    let (config, _) = load_config()?;
+    let device = config
+        .get_device(device_name)
+        .with_context(|| format!("unknown device {device_name}"))?;
+    let hex_digits: String = hex_bytes.split_whitespace().collect();
+    let bytes = hex::decode(&hex_digits).context("invalid hex bytes")?;
+
+    let written = device.send_report(&bytes)?;
+    if json {
+        return print_json(&json!({ "device": device.name, "interfaces": written }));
+    }
+    println!("Sent {} bytes to {} interface(s)", bytes.len(), written);
+    Ok(())
+}
+
+fn rules_list(json: bool) -> Result<()> {
+    let (config, _) = load_config()?;
+    let rules: Vec<RuleSummary> = config.rules.iter().map(Rule::summary).collect();
+
+    if json {
+        return print_json(&rules);
+    }
+    for rule in rules {
+        let targets: Vec<String> = rule
+            .devices
+            .iter()
+            .cloned()
+            .chain(rule.groups.iter().map(|group| format!("group {group}")))
+            .collect();
+        println!(
+            "{}\t{}\t{}\t{}",
+            rule.name,
+            rule.event,
+            if rule.enabled { "enabled" } else { "disabled" },
+            targets.join(", ")
+        );
+    }
+    Ok(())
+}
+
+// Returns whether the config is valid.
+fn config_validate(json: bool) -> Result<bool> {
+    let problems = match load_config() {
+        Ok((config, needs_migration)) => {
+            let mut problems = config.validate();
+            if needs_migration {
+                problems.push(
+                    "config.toml needs migration to the [[devices]] format, start the app once to migrate it"
+                        .to_string(),
+                );
+            }
+            problems
+        }
+        Err(e) => vec![format!("{e:#}")],
+    };
+    let valid = problems.is_empty();
+
+    if json {
+        print_json(&Validation { valid, problems })?;
+    } else if valid {
+        println!("{} is valid", Config::path()?.display());
+    } else {
+        for problem in problems {
+            println!("{problem}");
+        }
+    }
+    Ok(valid)
+}
+
+fn window_current(json: bool) -> Result<()> {
+    let window = win::query_focused_window()?;
+
+    if json {
+        return print_json(&window);
+    }
+    let or_none = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    println!("title: {}", or_none(window.title));
+    println!("class: {}", or_none(window.class));
+    println!(
+        "exe: {}",
+        or_none(window.exe.map(|exe| exe.display().to_string()))
+    );
+    println!(
+        "fullscreen: {}",
+        or_none(
+            window
+                .is_fullscreen
+                .map(|fullscreen| fullscreen.to_string())
+        )
+    );
+    println!("workspace: {}", or_none(window.workspace));
+    Ok(())
+}
+
+// Runs the subcommand, returning the process exit code.
+pub fn run(command: Command, json: bool) -> i32 {
+    let result = match command {
+        Command::Devices(DevicesCommand::List) => devices_list(json).map(|_| true),
+        Command::Send { device, hex } => send(&device, &hex, json).map(|_| true),
+        Command::Rules(RulesCommand::List) => rules_list(json).map(|_| true),
+        Command::Config(ConfigCommand::Validate) => config_validate(json),
+        Command::Window(WindowCommand::Current) => window_current(json).map(|_| true),
+    };
+
+    match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            if json {
+                println!("{}", json!({ "error": format!("{e:#}") }));
+            } else {
+                eprintln!("{:#}", e);
+            }
+            1
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tokio::sync::watch;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ClipboardContent {
+    pub text: Option<String>,
+    pub has_image: bool,
+    pub has_files: bool,
+    // Flagged by a password manager to be kept out of clipboard history.
+    pub is_sensitive: bool,
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ClipboardContentType {
+    #[default]
+    Any,
+    Text,
+    Image,
+    Files,
+    Sensitive,
+}
+
+impl ClipboardContent {
+    pub fn has(&self, content_type: ClipboardContentType) -> bool {
+        match content_type {
+            ClipboardContentType::Any => true,
+            ClipboardContentType::Text => self.text.is_some(),
+            ClipboardContentType::Image => self.has_image,
+            ClipboardContentType::Files => self.has_files,
+            ClipboardContentType::Sensitive => self.is_sensitive,
+        }
+    }
+}
+
+pub static CLIPBOARD_TX: LazyLock<watch::Sender<ClipboardContent>> = LazyLock::new(|| {
+    let (tx, _rx) = watch::channel(ClipboardContent::default());
+    tx
+});
+
+// Copying the same content again is still a change, e.g. to warn again about
+// a copied secret.
+#[cfg(any(target_os = "linux", windows))]
+fn set_clipboard(content: ClipboardContent) {
+    let _ = CLIPBOARD_TX.send(content);
+}
+
+pub fn start_clipboard_watcher() {
+    let spawned = std::thread::Builder::new()
+        .name("clipboard-watcher".into())
+        .spawn(|| {
+            if let Err(e) = watch_clipboard() {
+                tracing::error!("start_clipboard_watcher: {}", e);
+            }
+        });
+
+    if let Err(e) = spawned {
+        tracing::error!("start_clipboard_watcher: failed to spawn thread: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn watch_clipboard() -> anyhow::Result<()> {
+    x11::watch()
+}
+
+#[cfg(windows)]
+fn watch_clipboard() -> anyhow::Result<()> {
+    win32::watch()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn watch_clipboard() -> anyhow::Result<()> {
+    anyhow::bail!("clipboard watching is not supported on this platform")
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use anyhow::{Context, Result};
+    use std::time::{Duration, Instant};
+    use x11rb::{
+        COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME, NONE,
+        connection::Connection,
+        protocol::{
+            Event,
+            xfixes::{ConnectionExt as _, SelectionEventMask},
+            xproto::{AtomEnum, ConnectionExt, CreateWindowAux, Window, WindowClass},
+        },
+        rust_connection::RustConnection,
+    };
+
+    use super::{ClipboardContent, set_clipboard};
+
+    // Owners that never answer a conversion request are given up on.
+    const CONVERT_TIMEOUT: Duration = Duration::from_secs(1);
+    const TEXT_TARGETS: [&str; 5] = [
+        "UTF8_STRING",
+        "STRING",
+        "TEXT",
+        "text/plain;charset=utf-8",
+        "text/plain",
+    ];
+    // Set by KeePassXC and KDE password managers.
+    const SENSITIVE_TARGET: &str = "x-kde-passwordManagerHint";
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            CLIPBOARD,
+            TARGETS,
+            UTF8_STRING,
+            INCR,
+            LOCKEDIN_CLIPBOARD,
+        }
+    }
+
+    struct ClipboardReader {
+        conn: RustConnection,
+        window: Window,
+        atoms: Atoms,
+        // The clipboard changed again while it was being read.
+        changed: bool,
+    }
+
+    pub fn watch() -> Result<()> {
+        let (conn, screen) = x11rb::connect(None).context("failed to connect to X11")?;
+        let root = conn.setup().roots[screen].root;
+        let atoms = Atoms::new(&conn)?.reply()?;
+        conn.xfixes_query_version(5, 0)?
+            .reply()
+            .context("XFixes is not available")?;
+
+        // Converted selections are delivered to a window's property.
+        let window = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )?;
+        conn.xfixes_select_selection_input(
+            window,
+            atoms.CLIPBOARD,
+            SelectionEventMask::SET_SELECTION_OWNER
+                | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+        )?;
+        conn.flush()?;
+
+        let mut reader = ClipboardReader {
+            conn,
+            window,
+            atoms,
+            changed: true,
+        };
+        loop {
+            while reader.changed {
+                reader.changed = false;
+                let content = reader.read();
+                set_clipboard(content);
+            }
+            if let Event::XfixesSelectionNotify(_) = reader.conn.wait_for_event()? {
+                reader.changed = true;
+            }
+        }
+    }
+
+    impl ClipboardReader {
+        fn read(&mut self) -> ClipboardContent {
+            let has_owner = self
+                .conn
+                .get_selection_owner(self.atoms.CLIPBOARD)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .is_some_and(|reply| reply.owner != NONE);
+            if !has_owner {
+                return ClipboardContent::default();
+            }
+
+            let targets: Vec<String> = self
+                .convert(self.atoms.TARGETS)
+                .and_then(|reply| Some(reply.value32()?.collect::<Vec<_>>()))
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|atom| self.atom_name(atom))
+                .collect();
+            let has_target = |name: &str| targets.iter().any(|target| target == name);
+
+            let text = if TEXT_TARGETS.iter().any(|name| has_target(name)) {
+                // Large selections are sent in chunks (INCR), those aren't read.
+                self.convert(self.atoms.UTF8_STRING)
+                    .filter(|reply| reply.type_ != self.atoms.INCR)
+                    .map(|reply| String::from_utf8_lossy(&reply.value).into_owned())
+            } else {
+                None
+            };
+
+            ClipboardContent {
+                text,
+                has_image: targets.iter().any(|target| target.starts_with("image/")),
+                has_files: has_target("text/uri-list"),
+                is_sensitive: has_target(SENSITIVE_TARGET),
+            }
+        }
+
+        fn atom_name(&self, atom: u32) -> Option<String> {
+            let reply = self.conn.get_atom_name(atom).ok()?.reply().ok()?;
+            Some(String::from_utf8_lossy(&reply.name).into_owned())
+        }
+
+        fn convert(&mut self, target: u32) -> Option<x11rb::protocol::xproto::GetPropertyReply> {
+            self.conn
+                .convert_selection(
+                    self.window,
+                    self.atoms.CLIPBOARD,
+                    target,
+                    self.atoms.LOCKEDIN_CLIPBOARD,
+                    CURRENT_TIME,
+                )
+                .ok()?;
+            self.conn.flush().ok()?;
+
+            let deadline = Instant::now() + CONVERT_TIMEOUT;
+            loop {
+                match self.conn.poll_for_event().ok()? {
+                    Some(Event::SelectionNotify(event)) if event.requestor == self.window => {
+                        if event.property == NONE {
+                            return None;
+                        }
+                        break;
+                    }
+                    Some(Event::XfixesSelectionNotify(_)) => self.changed = true,
+                    Some(_) => {}
+                    None if Instant::now() >= deadline => return None,
+                    None => std::thread::sleep(Duration::from_millis(10)),
+                }
+            }
+
+            self.conn
+                .get_property(
+                    true,
+                    self.window,
+                    self.atoms.LOCKEDIN_CLIPBOARD,
+                    AtomEnum::ANY,
+                    0,
+                    u32::MAX / 4,
+                )
+                .ok()?
+                .reply()
+                .ok()
+        }
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use anyhow::Result;
+    use windows::{
+        Win32::{
+            Foundation::{HGLOBAL, HWND, LPARAM, LRESULT, WPARAM},
+            System::{
+                DataExchange::{
+                    AddClipboardFormatListener, CloseClipboard, GetClipboardData,
+                    IsClipboardFormatAvailable, OpenClipboard, RegisterClipboardFormatW,
+                },
+                LibraryLoader::GetModuleHandleW,
+                Memory::{GlobalLock, GlobalSize, GlobalUnlock},
+                Ole::{CF_BITMAP, CF_DIB, CF_HDROP, CF_UNICODETEXT},
+            },
+            UI::WindowsAndMessaging::{
+                CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, HWND_MESSAGE, MSG,
+                RegisterClassW, WINDOW_EX_STYLE, WINDOW_STYLE, WM_CLIPBOARDUPDATE, WNDCLASSW,
+            },
+        },
+        core::w,
+    };
+
+    use super::{ClipboardContent, set_clipboard};
+
+    // The clipboard is briefly held open by whoever just wrote to it.
+    const OPEN_ATTEMPTS: usize = 5;
+
+    fn format_available(format: u32) -> bool {
+        unsafe { IsClipboardFormatAvailable(format).is_ok() }
+    }
+
+    fn read_text() -> Option<String> {
+        unsafe {
+            let handle = GetClipboardData(u32::from(CF_UNICODETEXT.0)).ok()?;
+            let global = HGLOBAL(handle.0);
+            let ptr = GlobalLock(global) as *const u16;
+            if ptr.is_null() {
+                return None;
+            }
+            let len = GlobalSize(global) / std::mem::size_of::<u16>();
+            let chars = std::slice::from_raw_parts(ptr, len);
+            let end = chars.iter().position(|&c| c == 0).unwrap_or(len);
+            let text = String::from_utf16_lossy(&chars[..end]);
+            let _ = GlobalUnlock(global);
+            Some(text)
+        }
+    }
+
+    fn read() -> ClipboardContent {
+        let opened = (0..OPEN_ATTEMPTS).any(|_| {
+            let opened = unsafe { OpenClipboard(None).is_ok() };
+            if !opened {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            opened
+        });
+        if !opened {
+            tracing::error!("clipboard watcher: failed to open the clipboard");
+            return ClipboardContent::default();
+        }
+
+        // Set by password managers to keep secrets out of clipboard history.
+        let sensitive_format =
+            unsafe { RegisterClipboardFormatW(w!("ExcludeClipboardContentFromMonitorProcessing")) };
+        let content = ClipboardContent {
+            text: read_text(),
+            has_image: format_available(u32::from(CF_DIB.0))
+                || format_available(u32::from(CF_BITMAP.0)),
+            has_files: format_available(u32::from(CF_HDROP.0)),
+            is_sensitive: sensitive_format != 0 && format_available(sensitive_format),
+        };
+
+        unsafe {
+            let _ = CloseClipboard();
+        }
+        content
+    }
+
+    extern "system" fn window_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg == WM_CLIPBOARDUPDATE {
+            set_clipboard(read());
+            return LRESULT(0);
+        }
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+    }
+
+    pub fn watch() -> Result<()> {
+        unsafe {
+            let instance = GetModuleHandleW(None)?;
+            let class_name = w!("LockedInClipboardListener");
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance.into(),
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            anyhow::ensure!(
+                RegisterClassW(&class) != 0,
+                "failed to register the clipboard listener window class"
+            );
+
+            // A message-only window, it only exists to receive clipboard updates.
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                w!(""),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                Some(HWND_MESSAGE),
+                None,
+                Some(instance.into()),
+                None,
+            )?;
+            AddClipboardFormatListener(hwnd)?;
+
+            set_clipboard(read());
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                DispatchMessageW(&msg);
+            }
+        }
+        Ok(())
+    }
+}
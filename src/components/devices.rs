@@ -1,6 +1,7 @@
 use dioxus::prelude::*;
 
 use crate::{
+    CONFIG_SIGNAL,
     components::{dialog::Dialog, edit_device::EditDevice},
     config,
 };
@@ -10,14 +11,9 @@ struct EditTarget {
     index: Option<usize>, // None = "Add", Some(i) = "Edit existing i"
 }
 
-#[derive(Props, PartialEq, Clone)]
-pub struct DevicesProps {
-    pub devices: Signal<Vec<config::Device>>,
-}
-
+// Manages the shared device registry that rules reference by name.
 #[component]
-pub fn Devices(props: DevicesProps) -> Element {
-    let mut devices_signal = props.devices;
+pub fn Devices() -> Element {
     let mut show_device_editor = use_signal(|| false);
     let mut draft_device = use_signal(config::Device::default);
     let mut edit_target = use_signal(|| None::<EditTarget>);
@@ -25,7 +21,7 @@ pub fn Devices(props: DevicesProps) -> Element {
     rsx!(
         div {
             class: "devices",
-            for (i, device) in props.devices.read().iter().enumerate() {
+            for (i, device) in CONFIG_SIGNAL.read().devices.iter().enumerate() {
                 details {
                     class: "device",
                     summary { "{device.name}" }
@@ -36,6 +32,16 @@ pub fn Devices(props: DevicesProps) -> Element {
                         li { "{device.usage}" }
                         li { "{device.report_length}" }
                         li { "{device.report_id}" }
+                        if let Some(serial_number) = &device.serial_number {
+                            li { "Serial: {serial_number}" }
+                        }
+                        if let Some(interface_number) = device.interface_number {
+                            li { "Interface: {interface_number}" }
+                        }
+                        if let Some(path) = &device.path {
+                            li { "Path: {path}" }
+                        }
+                        li { "Target: {device.target}" }
                     }
                     div {
                         role: "group",
@@ -54,8 +60,11 @@ pub fn Devices(props: DevicesProps) -> Element {
                         },
                         button {
                             class: "danger",
-                            onclick: move |_| {
-                                devices_signal.write().remove(i);
+                            onclick: {
+                                let name = device.name.clone();
+                                move |_| {
+                                    CONFIG_SIGNAL.write().delete_device(&name);
+                                }
                             },
                             "Delete"
                         }
@@ -65,6 +74,7 @@ pub fn Devices(props: DevicesProps) -> Element {
             button {
                 class: "outline",
                 onclick: move |_| {
+                    draft_device.set(config::Device::default());
                     edit_target.set(Some(EditTarget { index: None }));
                     show_device_editor.set(true);
                 },
@@ -83,11 +93,15 @@ pub fn Devices(props: DevicesProps) -> Element {
                             show_device_editor.set(false);
                             return;
                         };
-                        let new_device = std::mem::take(&mut *draft_device.write());
+                        let mut new_device = std::mem::take(&mut *draft_device.write());
+                        let mut config = CONFIG_SIGNAL.write();
+                        new_device.name = config.unique_device_name(&new_device.name, target.index);
                         if let Some(i) = target.index {
-                            devices_signal.write()[i] = new_device;
+                            let old_name = config.devices[i].name.clone();
+                            config.rename_device(&old_name, &new_device.name);
+                            config.devices[i] = new_device;
                         } else {
-                            devices_signal.write().push(new_device);
+                            config.devices.push(new_device);
                         }
                         edit_target.set(None);
                         show_device_editor.set(false);
@@ -1,4 +1,5 @@
 use dioxus::prelude::*;
+use strum::IntoEnumIterator;
 
 use crate::{
     components::{dialog::Dialog, hid_devices::HidDevices},
@@ -28,6 +29,18 @@ pub fn EditDevice(props: EditDeviceProps) -> Element {
         )
     };
 
+    let (serial_number, interface_number, path, target) = {
+        let d = device.read();
+        (
+            d.serial_number.clone().unwrap_or_default(),
+            d.interface_number
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            d.path.clone().unwrap_or_default(),
+            d.target,
+        )
+    };
+
     let mut show_device_search = use_signal(|| false);
 
     rsx!(
@@ -124,6 +137,61 @@ pub fn EditDevice(props: EditDeviceProps) -> Element {
                         }
                     }
                 }
+                label {
+                    "Serial Number",
+                    input {
+                        name: "serial_number",
+                        placeholder: "any",
+                        value: "{serial_number}",
+                        oninput: move |e| {
+                            let value = e.value().trim().to_string();
+                            device.write().serial_number = if value.is_empty() { None } else { Some(value) };
+                        }
+                    }
+                }
+                label {
+                    "Interface Number",
+                    input {
+                        type: "number",
+                        name: "interface_number",
+                        placeholder: "any",
+                        value: "{interface_number}",
+                        oninput: move |e| {
+                            let value = e.value().trim().to_string();
+                            device.write().interface_number = value.parse::<i32>().ok();
+                        }
+                    }
+                }
+                label {
+                    "Path",
+                    input {
+                        name: "path",
+                        placeholder: "any",
+                        value: "{path}",
+                        oninput: move |e| {
+                            let value = e.value().trim().to_string();
+                            device.write().path = if value.is_empty() { None } else { Some(value) };
+                        }
+                    }
+                }
+                label {
+                    "Target",
+                    select {
+                        name: "target",
+                        aria_label: "Select which matching devices to send to",
+                        oninput: move |e| {
+                            if let Ok(target) = e.value().parse::<config::DeviceTarget>() {
+                                device.write().target = target;
+                            }
+                        },
+                        for option_target in config::DeviceTarget::iter() {
+                            option {
+                                selected: option_target == target,
+                                "{option_target}"
+                            }
+                        }
+                    }
+                }
             },
             div {
                 class: "grid",
@@ -0,0 +1,145 @@
+use dioxus::prelude::*;
+
+use crate::{CONFIG_SIGNAL, config};
+
+#[derive(Props, PartialEq, Clone)]
+pub struct EditGroupProps {
+    pub group: Signal<config::DeviceGroup>,
+    pub on_submit: EventHandler<()>,
+}
+
+#[component]
+pub fn EditGroup(props: EditGroupProps) -> Element {
+    let mut group = props.group;
+
+    let name = group.read().name.clone();
+
+    let device_names: Vec<String> = CONFIG_SIGNAL
+        .read()
+        .devices
+        .iter()
+        .map(|d| d.name.clone())
+        .collect();
+
+    rsx!(
+        form {
+            class: "edit-group",
+            fieldset {
+                label {
+                    "Name",
+                    input {
+                        name: "name",
+                        placeholder: "desk lights",
+                        value: "{name}",
+                        oninput: move |e| {
+                            let value = e.value().trim().to_string();
+                            group.write().name = value;
+                        }
+                    }
+                }
+            }
+            fieldset {
+                legend { "Members" }
+                for device_name in device_names {
+                    {
+                        let member = group
+                            .read()
+                            .members
+                            .iter()
+                            .find(|m| m.device == device_name)
+                            .cloned();
+                        let report_id = member
+                            .as_ref()
+                            .and_then(|m| m.report_id)
+                            .map(|id| id.to_string())
+                            .unwrap_or_default();
+                        let report_length = member
+                            .as_ref()
+                            .and_then(|m| m.report_length)
+                            .map(|length| length.to_string())
+                            .unwrap_or_default();
+
+                        rsx! {
+                            div {
+                                class: "edit-group__member",
+                                label {
+                                    input {
+                                        type: "checkbox",
+                                        name: "members",
+                                        checked: member.is_some(),
+                                        onchange: {
+                                            let device_name = device_name.clone();
+                                            move |e: FormEvent| {
+                                                let mut group = group.write();
+                                                group.members.retain(|m| m.device != device_name);
+                                                if e.checked() {
+                                                    group.members.push(config::GroupMember {
+                                                        device: device_name.clone(),
+                                                        ..Default::default()
+                                                    });
+                                                }
+                                            }
+                                        }
+                                    }
+                                    "{device_name}"
+                                }
+                                if member.is_some() {
+                                    div {
+                                        class: "grid",
+                                        input {
+                                            type: "number",
+                                            name: "report_id",
+                                            placeholder: "Report ID (device default)",
+                                            value: "{report_id}",
+                                            oninput: {
+                                                let device_name = device_name.clone();
+                                                move |e: FormEvent| {
+                                                    let value = e.value().trim().to_string();
+                                                    if let Some(member) = group
+                                                        .write()
+                                                        .members
+                                                        .iter_mut()
+                                                        .find(|m| m.device == device_name)
+                                                    {
+                                                        member.report_id = value.parse::<u8>().ok();
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        input {
+                                            type: "number",
+                                            name: "report_length",
+                                            placeholder: "Report Length (device default)",
+                                            value: "{report_length}",
+                                            oninput: {
+                                                let device_name = device_name.clone();
+                                                move |e: FormEvent| {
+                                                    let value = e.value().trim().to_string();
+                                                    if let Some(member) = group
+                                                        .write()
+                                                        .members
+                                                        .iter_mut()
+                                                        .find(|m| m.device == device_name)
+                                                    {
+                                                        member.report_length = value.parse::<u16>().ok();
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            input {
+                type: "submit",
+                onclick: move |_| {
+                    props.on_submit.call(());
+                },
+                "Submit",
+            }
+        }
+    )
+}
@@ -0,0 +1,429 @@
+use dioxus::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{CONFIG_SIGNAL, config, qmk, template};
+
+const HEX_FORMAT: &str = "hex";
+const LIBRARY_FORMAT: &str = "library";
+const STEP_FORMAT: &str = "step";
+const TEMPLATE_FORMAT: &str = "template";
+
+#[derive(Props, PartialEq, Clone)]
+pub struct EditReportProps {
+    pub report: Signal<config::Report>,
+}
+
+#[component]
+pub fn EditReport(props: EditReportProps) -> Element {
+    let mut report = props.report;
+
+    let format = match &*report.read() {
+        config::Report::Bytes(_) => HEX_FORMAT.to_string(),
+        config::Report::Named(_) => LIBRARY_FORMAT.to_string(),
+        config::Report::Qmk(preset) => preset.to_string(),
+        config::Report::Step(_) => STEP_FORMAT.to_string(),
+        config::Report::Template(_) => TEMPLATE_FORMAT.to_string(),
+    };
+
+    let library_names: Vec<String> = CONFIG_SIGNAL
+        .read()
+        .reports
+        .iter()
+        .map(|r| r.name.clone())
+        .collect();
+
+    rsx!(
+        div {
+            class: "edit-report",
+            label {
+                "Format",
+                select {
+                    name: "format",
+                    aria_label: "Select a report format",
+                    oninput: move |e| {
+                        let value = e.value();
+                        if value == HEX_FORMAT {
+                            report.set(config::Report::Bytes(Vec::new()));
+                        } else if value == LIBRARY_FORMAT {
+                            report.set(config::Report::Named(String::new()));
+                        } else if value == STEP_FORMAT {
+                            report.set(config::Report::Step(config::ReportStep::default()));
+                        } else if value == TEMPLATE_FORMAT {
+                            report.set(config::Report::Template(config::TemplateReport::default()));
+                        } else if let Ok(preset) = value.parse::<qmk::Preset>() {
+                            report.set(config::Report::Qmk(preset));
+                        }
+                    },
+                    option {
+                        selected: format == HEX_FORMAT,
+                        "{HEX_FORMAT}"
+                    }
+                    option {
+                        selected: format == LIBRARY_FORMAT,
+                        "{LIBRARY_FORMAT}"
+                    }
+                    option {
+                        selected: format == STEP_FORMAT,
+                        "{STEP_FORMAT}"
+                    }
+                    option {
+                        selected: format == TEMPLATE_FORMAT,
+                        "{TEMPLATE_FORMAT}"
+                    }
+                    for preset in qmk::Preset::iter().map(|preset| preset.to_string()) {
+                        option {
+                            selected: preset == format,
+                            "{preset}"
+                        }
+                    }
+                }
+            }
+            {
+                let current = report.read().clone();
+                match current {
+                    config::Report::Bytes(bytes) => rsx! {
+                        input {
+                            name: "report",
+                            value: hex::encode(bytes),
+                            oninput: move |e| {
+                                if let Ok(value) = hex::decode(e.value()) {
+                                    report.set(config::Report::Bytes(value));
+                                }
+                            }
+                        }
+                    },
+                    config::Report::Named(name) => rsx! {
+                        select {
+                            name: "library_report",
+                            aria_label: "Select a report from the library",
+                            oninput: move |e| {
+                                report.set(config::Report::Named(e.value()));
+                            },
+                            option {
+                                selected: name.is_empty(),
+                                disabled: true,
+                                "Select a report"
+                            }
+                            for library_name in library_names {
+                                option {
+                                    selected: library_name == name,
+                                    "{library_name}"
+                                }
+                            }
+                        }
+                    },
+                    config::Report::Qmk(preset) => rsx! {
+                        PresetFields {
+                            preset: preset,
+                            on_change: move |preset| report.set(config::Report::Qmk(preset)),
+                        }
+                    },
+                    config::Report::Step(_) => rsx! {
+                        StepFields {
+                            report: report,
+                        }
+                    },
+                    config::Report::Template(_) => rsx! {
+                        TemplateFields {
+                            report: report,
+                        }
+                    },
+                }
+            }
+        }
+    )
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct TemplateFieldsProps {
+    report: Signal<config::Report>,
+}
+
+#[component]
+fn TemplateFields(props: TemplateFieldsProps) -> Element {
+    let mut report = props.report;
+
+    // Keep the raw text so it can be edited while it doesn't parse yet.
+    let mut text = use_signal(|| match &*report.read() {
+        config::Report::Template(template_report) => template_report.template.to_string(),
+        _ => String::new(),
+    });
+    let mut error = use_signal(|| None::<String>);
+
+    rsx!(
+        label {
+            "Template",
+            input {
+                name: "template",
+                placeholder: "01 {{title:ascii:20}} {{time:hh}}{{time:mm}} {{crc8}}",
+                value: "{text}",
+                aria_invalid: error.read().is_some(),
+                oninput: move |e| {
+                    let value = e.value();
+                    match value.parse::<template::Template>() {
+                        Ok(template) => {
+                            report.set(config::Report::Template(config::TemplateReport { template }));
+                            error.set(None);
+                        }
+                        Err(e) => error.set(Some(format!("{e:#}"))),
+                    }
+                    text.set(value);
+                }
+            }
+            if let Some(error) = error() {
+                small { "{error}" }
+            } else {
+                small { "Hex bytes with {{title:utf8|ascii:LENGTH}}, {{media:artist|title|album:utf8|ascii:LENGTH}}, {{media:status}}, {{time:hh|mm|ss}}, {{crc8}}, {{cpu|ram|disk|net_rx|net_tx[:FROM_MIN:FROM_MAX[:TO_MIN:TO_MAX]]}}, and for webhook rules {{body:FIELD}} and {{body:FIELD:utf8|ascii:LENGTH}} placeholders." }
+            }
+        }
+    )
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct StepFieldsProps {
+    report: Signal<config::Report>,
+}
+
+#[component]
+fn StepFields(props: StepFieldsProps) -> Element {
+    let mut report = props.report;
+
+    let step = match &*report.read() {
+        config::Report::Step(step) => step.clone(),
+        _ => config::ReportStep::default(),
+    };
+
+    // The report to send is edited with a nested editor, an empty report
+    // makes the step a pause.
+    let send = use_signal(|| step.send.as_deref().cloned().unwrap_or_default());
+    use_effect(move || {
+        let send = send();
+        if let config::Report::Step(step) = &mut *report.write() {
+            step.send = (send != config::Report::default()).then(|| Box::new(send));
+        }
+    });
+
+    let device_names: Vec<String> = CONFIG_SIGNAL
+        .read()
+        .devices
+        .iter()
+        .map(|d| d.name.clone())
+        .collect();
+
+    rsx!(
+        fieldset {
+            class: "edit-report__step",
+            legend { "Send" }
+            EditReport {
+                report: send,
+            }
+            label {
+                "Wait (ms)",
+                input {
+                    type: "number",
+                    name: "wait_ms",
+                    min: 0,
+                    value: "{step.wait_ms}",
+                    oninput: move |e| {
+                        if let Ok(value) = e.value().trim().parse::<u64>()
+                            && let config::Report::Step(step) = &mut *report.write()
+                        {
+                            step.wait_ms = value;
+                        }
+                    }
+                }
+            }
+            label {
+                "Repeat",
+                input {
+                    type: "number",
+                    name: "repeat",
+                    min: 1,
+                    value: "{step.repeat}",
+                    oninput: move |e| {
+                        if let Ok(value) = e.value().trim().parse::<u32>()
+                            && let config::Report::Step(step) = &mut *report.write()
+                        {
+                            step.repeat = value;
+                        }
+                    }
+                }
+            }
+            label {
+                "Device",
+                select {
+                    name: "device",
+                    aria_label: "Only send to this device",
+                    oninput: move |e| {
+                        let value = e.value();
+                        if let config::Report::Step(step) = &mut *report.write() {
+                            step.device = if value.is_empty() { None } else { Some(value) };
+                        }
+                    },
+                    option {
+                        value: "",
+                        selected: step.device.is_none(),
+                        "All devices"
+                    }
+                    for device_name in device_names {
+                        option {
+                            selected: step.device.as_deref() == Some(device_name.as_str()),
+                            "{device_name}"
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct PresetFieldsProps {
+    preset: qmk::Preset,
+    on_change: EventHandler<qmk::Preset>,
+}
+
+#[component]
+fn PresetFields(props: PresetFieldsProps) -> Element {
+    let preset = props.preset;
+
+    let (channel, fields): (Option<qmk::RgbChannel>, Vec<(&'static str, u16)>) = match preset {
+        qmk::Preset::SwitchLayer { command, layer } => (
+            None,
+            vec![("command", command.into()), ("layer", layer.into())],
+        ),
+        qmk::Preset::RgbMode { channel, mode } => (Some(channel), vec![("mode", mode.into())]),
+        qmk::Preset::RgbHsv {
+            channel,
+            hue,
+            saturation,
+            value,
+        } => (
+            Some(channel),
+            vec![
+                ("hue", hue.into()),
+                ("saturation", saturation.into()),
+                ("value", value.into()),
+            ],
+        ),
+        qmk::Preset::ViaGetKeycode { layer, row, column } => (
+            None,
+            vec![
+                ("layer", layer.into()),
+                ("row", row.into()),
+                ("column", column.into()),
+            ],
+        ),
+        qmk::Preset::ViaSetKeycode {
+            layer,
+            row,
+            column,
+            keycode,
+        } => (
+            None,
+            vec![
+                ("layer", layer.into()),
+                ("row", row.into()),
+                ("column", column.into()),
+                ("keycode", keycode),
+            ],
+        ),
+    };
+
+    rsx!(
+        fieldset {
+            class: "edit-report__preset",
+            if let Some(channel) = channel {
+                label {
+                    "Channel",
+                    select {
+                        name: "channel",
+                        oninput: move |e| {
+                            let Ok(new_channel) = e.value().parse::<qmk::RgbChannel>() else {
+                                return;
+                            };
+                            let mut preset = props.preset;
+                            if let qmk::Preset::RgbMode { channel, .. }
+                            | qmk::Preset::RgbHsv { channel, .. } = &mut preset
+                            {
+                                *channel = new_channel;
+                            }
+                            props.on_change.call(preset);
+                        },
+                        for option_channel in qmk::RgbChannel::iter() {
+                            option {
+                                selected: option_channel == channel,
+                                "{option_channel}"
+                            }
+                        }
+                    }
+                }
+            }
+            for (field, value) in fields {
+                label {
+                    "{field}",
+                    input {
+                        type: "number",
+                        name: field,
+                        min: 0,
+                        max: if field == "keycode" { u16::MAX } else { u8::MAX as u16 },
+                        value: "{value}",
+                        oninput: move |e| {
+                            if let Ok(value) = e.value().trim().parse::<u16>() {
+                                props.on_change.call(with_field(props.preset, field, value));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+// Returns a copy of the preset with the named field set, so each input only
+// needs to know its field name.
+fn with_field(mut preset: qmk::Preset, field: &str, value: u16) -> qmk::Preset {
+    let byte = value.min(u8::MAX as u16) as u8;
+    match &mut preset {
+        qmk::Preset::SwitchLayer { command, layer } => match field {
+            "command" => *command = byte,
+            "layer" => *layer = byte,
+            _ => {}
+        },
+        qmk::Preset::RgbMode { mode, .. } => {
+            if field == "mode" {
+                *mode = byte;
+            }
+        }
+        qmk::Preset::RgbHsv {
+            hue,
+            saturation,
+            value: v,
+            ..
+        } => match field {
+            "hue" => *hue = byte,
+            "saturation" => *saturation = byte,
+            "value" => *v = byte,
+            _ => {}
+        },
+        qmk::Preset::ViaGetKeycode { layer, row, column } => match field {
+            "layer" => *layer = byte,
+            "row" => *row = byte,
+            "column" => *column = byte,
+            _ => {}
+        },
+        qmk::Preset::ViaSetKeycode {
+            layer,
+            row,
+            column,
+            keycode,
+        } => match field {
+            "layer" => *layer = byte,
+            "row" => *row = byte,
+            "column" => *column = byte,
+            "keycode" => *keycode = value,
+            _ => {}
+        },
+    }
+    preset
+}
@@ -0,0 +1,61 @@
+use dioxus::prelude::*;
+
+use crate::{components::report_list::ReportList, config};
+
+#[derive(Props, PartialEq, Clone)]
+pub struct EditReportDefinitionProps {
+    pub definition: Signal<config::ReportDefinition>,
+    pub on_submit: EventHandler<()>,
+}
+
+#[component]
+pub fn EditReportDefinition(props: EditReportDefinitionProps) -> Element {
+    let mut definition = props.definition;
+
+    let (name, description, reports) = {
+        let d = definition.read();
+        (d.name.clone(), d.description.clone(), d.reports.clone())
+    };
+
+    rsx!(
+        form {
+            class: "edit-report-definition",
+            fieldset {
+                label {
+                    "Name",
+                    input {
+                        name: "name",
+                        placeholder: "layer 1",
+                        value: "{name}",
+                        oninput: move |e| {
+                            let value = e.value().trim().to_string();
+                            definition.write().name = value;
+                        }
+                    }
+                }
+                label {
+                    "Description",
+                    textarea {
+                        name: "description",
+                        value: "{description}",
+                        oninput: move |e| {
+                            definition.write().description = e.value();
+                        }
+                    }
+                }
+            }
+            h6 { "Reports" }
+            ReportList {
+                reports: reports,
+                on_change: move |reports| definition.write().reports = reports,
+            }
+            input {
+                type: "submit",
+                onclick: move |_| {
+                    props.on_submit.call(());
+                },
+                "Submit",
+            }
+        }
+    )
+}
@@ -3,7 +3,10 @@ use strum::IntoEnumIterator;
 
 use crate::{
     CONFIG_SIGNAL,
-    components::{devices::Devices, events::event_configurator::EventConfigurator},
+    components::{
+        events::event_configurator::EventConfigurator, select_devices::SelectDevices,
+        test_rule::TestRule,
+    },
     config::{self},
 };
 
@@ -25,6 +28,7 @@ pub fn EditRule(props: EditRuleProps) -> Element {
 
     let mut event_signal = use_signal(|| rule().event);
     let mut devices_signal = use_signal(|| rule().devices);
+    let mut groups_signal = use_signal(|| rule().groups);
 
     rsx! {
         form {
@@ -63,8 +67,18 @@ pub fn EditRule(props: EditRuleProps) -> Element {
                 hr {},
                 label {
                     "Devices",
-                    Devices {
+                    SelectDevices {
                         devices: devices_signal,
+                        groups: groups_signal,
+                    }
+                }
+                details {
+                    summary { "Test this rule" }
+                    TestRule {
+                        name: rule().name,
+                        event: event_signal,
+                        devices: devices_signal,
+                        groups: groups_signal,
                     }
                 }
             }
@@ -74,6 +88,7 @@ pub fn EditRule(props: EditRuleProps) -> Element {
                     let mut config = CONFIG_SIGNAL.write();
                     rule.write().event = std::mem::take(&mut *event_signal.write());
                     rule.write().devices = std::mem::take(&mut *devices_signal.write());
+                    rule.write().groups = std::mem::take(&mut *groups_signal.write());
                     if let Some(index) = config.get_rule_index(&props.rule_name)
                     {
                         config.rules[index] = rule().clone();
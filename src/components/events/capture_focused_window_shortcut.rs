@@ -24,8 +24,14 @@ pub fn CaptureFocusedWindowShortcut(props: CaptureFocusedWindowShortcutProps) ->
             return;
         }
 
-        captured_window.set(Some(FOCUSED_WINDOW_SIGNAL.read().clone()));
-        println!("{:?}", captured_window());
+        // Fullscreen and the workspace are states rather than part of the
+        // window's identity, they are only matched when picked explicitly.
+        captured_window.set(Some(win::WindowMetadata {
+            is_fullscreen: None,
+            workspace: None,
+            ..FOCUSED_WINDOW_SIGNAL.read().clone()
+        }));
+        tracing::debug!("captured window {:?}", captured_window());
 
         app_window.set_visible(true);
         app_window.set_minimized(false);
@@ -0,0 +1,74 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    capture::CaptureDevice,
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+};
+
+#[component]
+pub fn CaptureInUse(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::CaptureInUse(event_cfg) = event_read.deref() else {
+        panic!("Expected CaptureInUse");
+    };
+    let device = event_cfg.device;
+
+    rsx!(
+        div {
+            class: "event-config--capture-in-use",
+            label {
+                "Device",
+                select {
+                    name: "device",
+                    aria_label: "Select the capture device to watch",
+                    oninput: move |e| {
+                        let mut event_signal = props.event;
+                        if let Ok(device) = e.value().parse::<CaptureDevice>()
+                            && let config::Event::CaptureInUse(event_cfg) = &mut *event_signal.write()
+                        {
+                            event_cfg.device = device;
+                        }
+                    },
+                    for option_device in CaptureDevice::iter() {
+                        option {
+                            selected: option_device == device,
+                            "{option_device}"
+                        }
+                    }
+                }
+            }
+            hr {}
+            div {
+                class: "grid",
+                div {
+                    h6 { "In Use Reports" },
+                    ReportList {
+                        reports: event_cfg.in_use_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::CaptureInUse(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.in_use_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "Idle Reports" }
+                    ReportList {
+                        reports: event_cfg.idle_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::CaptureInUse(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.idle_reports = reports;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
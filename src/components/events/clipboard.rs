@@ -0,0 +1,122 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    clipboard::ClipboardContentType,
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+};
+
+#[component]
+pub fn Clipboard(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::Clipboard(event_cfg) = event_read.deref() else {
+        panic!("Expected Clipboard");
+    };
+    let content_type = event_cfg.content_type;
+    let pattern = event_cfg.pattern.clone().unwrap_or_default();
+    let pattern_error = event_cfg
+        .pattern
+        .as_deref()
+        .and_then(|pattern| regex::Regex::new(pattern).err())
+        .map(|e| e.to_string());
+    let revert_after_secs = event_cfg
+        .revert_after_secs
+        .map(|secs| secs.to_string())
+        .unwrap_or_default();
+
+    rsx!(
+        div {
+            class: "event-config--clipboard",
+            div {
+                class: "grid",
+                label {
+                    "Content Type",
+                    select {
+                        name: "content_type",
+                        aria_label: "Select the kind of clipboard content to match",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let Ok(content_type) = e.value().parse::<ClipboardContentType>()
+                                && let config::Event::Clipboard(event_cfg) = &mut *event_signal.write()
+                            {
+                                event_cfg.content_type = content_type;
+                            }
+                        },
+                        for option_content_type in ClipboardContentType::iter() {
+                            option {
+                                selected: option_content_type == content_type,
+                                "{option_content_type}"
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Revert After (seconds)",
+                    input {
+                        type: "number",
+                        min: "1",
+                        placeholder: "never",
+                        value: "{revert_after_secs}",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Clipboard(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.revert_after_secs =
+                                    e.value().parse::<u64>().ok().map(|secs| secs.max(1));
+                            }
+                        }
+                    }
+                }
+            }
+            label {
+                "Pattern",
+                input {
+                    name: "pattern",
+                    placeholder: "regex, e.g. ^ghp_[A-Za-z0-9]{{36}}$",
+                    value: "{pattern}",
+                    aria_invalid: pattern_error.is_some(),
+                    oninput: move |e| {
+                        let mut event_signal = props.event;
+                        if let config::Event::Clipboard(event_cfg) = &mut *event_signal.write() {
+                            let value = e.value();
+                            event_cfg.pattern = if value.is_empty() { None } else { Some(value) };
+                        }
+                    }
+                }
+                if let Some(error) = &pattern_error {
+                    small { "{error}" }
+                }
+            }
+            hr {}
+            div {
+                class: "grid",
+                div {
+                    h6 { "On Match Reports" },
+                    ReportList {
+                        reports: event_cfg.on_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Clipboard(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_match_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "On No Match Reports" }
+                    ReportList {
+                        reports: event_cfg.on_no_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Clipboard(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_no_match_reports = reports;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
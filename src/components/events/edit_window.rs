@@ -14,7 +14,7 @@ pub struct EditWindowProps {
 pub fn EditWindow(props: EditWindowProps) -> Element {
     let mut window = props.window;
 
-    let (title, class, exe) = {
+    let (title, class, exe, is_fullscreen, workspace) = {
         let w = window.read();
         (
             w.title.clone().unwrap_or_default(),
@@ -23,6 +23,8 @@ pub fn EditWindow(props: EditWindowProps) -> Element {
                 .as_ref()
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_default(),
+            w.is_fullscreen,
+            w.workspace.clone().unwrap_or_default(),
         )
     };
 
@@ -70,6 +72,35 @@ pub fn EditWindow(props: EditWindowProps) -> Element {
                         }
                     }
                 }
+                label {
+                    "Workspace",
+                    input {
+                        name: "workspace",
+                        placeholder: "workspace name or index",
+                        value: "{workspace}",
+                        oninput: move |e| {
+                            let value = e.value().trim().to_string();
+                            window.write().workspace = if value.is_empty() { None } else { Some(value) };
+                        }
+                    }
+                }
+                label {
+                    "Fullscreen",
+                    select {
+                        name: "is_fullscreen",
+                        aria_label: "Match on whether the window is fullscreen",
+                        oninput: move |e| {
+                            window.write().is_fullscreen = match e.value().as_str() {
+                                "fullscreen" => Some(true),
+                                "windowed" => Some(false),
+                                _ => None,
+                            };
+                        },
+                        option { value: "any", selected: is_fullscreen.is_none(), "any" }
+                        option { value: "fullscreen", selected: is_fullscreen == Some(true), "fullscreen" }
+                        option { value: "windowed", selected: is_fullscreen == Some(false), "windowed" }
+                    }
+                }
             }
             input {
                 type: "submit",
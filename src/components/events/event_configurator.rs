@@ -3,7 +3,12 @@ use std::ops::Deref;
 use dioxus::prelude::*;
 
 use crate::{
-    components::events::focused_window_changed::FocusedWindowChanged,
+    components::events::{
+        capture_in_use::CaptureInUse, clipboard::Clipboard, file_watch::FileWatch,
+        focused_window_changed::FocusedWindowChanged, fullscreen_changed::FullscreenChanged,
+        interval::Interval, keyboard_layout_changed::KeyboardLayoutChanged, media::Media,
+        network::Network, power::Power, webhook::Webhook, workspace_changed::WorkspaceChanged,
+    },
     config::{self},
 };
 
@@ -18,5 +23,18 @@ pub fn EventConfigurator(props: EventConfiguratorProps) -> Element {
         config::Event::FocusedWindowChanged(_) => {
             rsx!(FocusedWindowChanged { event: props.event })
         }
+        config::Event::Interval(_) => rsx!(Interval { event: props.event }),
+        config::Event::Power(_) => rsx!(Power { event: props.event }),
+        config::Event::Network(_) => rsx!(Network { event: props.event }),
+        config::Event::KeyboardLayoutChanged(_) => {
+            rsx!(KeyboardLayoutChanged { event: props.event })
+        }
+        config::Event::FullscreenChanged(_) => rsx!(FullscreenChanged { event: props.event }),
+        config::Event::WorkspaceChanged(_) => rsx!(WorkspaceChanged { event: props.event }),
+        config::Event::CaptureInUse(_) => rsx!(CaptureInUse { event: props.event }),
+        config::Event::Media(_) => rsx!(Media { event: props.event }),
+        config::Event::Clipboard(_) => rsx!(Clipboard { event: props.event }),
+        config::Event::FileWatch(_) => rsx!(FileWatch { event: props.event }),
+        config::Event::Webhook(_) => rsx!(Webhook { event: props.event }),
     }
 }
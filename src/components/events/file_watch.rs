@@ -0,0 +1,183 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+};
+
+fn non_empty(value: String) -> Option<String> {
+    if value.trim().is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[component]
+pub fn FileWatch(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::FileWatch(event_cfg) = event_read.deref() else {
+        panic!("Expected FileWatch");
+    };
+
+    rsx!(
+        div {
+            class: "event-config--file-watch",
+            label {
+                "Path",
+                input {
+                    name: "path",
+                    placeholder: "~/.focus",
+                    value: "{event_cfg.path}",
+                    oninput: move |e| {
+                        let mut event_signal = props.event;
+                        if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write() {
+                            event_cfg.path = e.value();
+                        }
+                    }
+                }
+            }
+            div {
+                class: "grid",
+                div {
+                    h6 { "On Create Reports" },
+                    ReportList {
+                        reports: event_cfg.on_create_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_create_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "On Modify Reports" },
+                    ReportList {
+                        reports: event_cfg.on_modify_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_modify_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "On Delete Reports" }
+                    ReportList {
+                        reports: event_cfg.on_delete_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_delete_reports = reports;
+                            }
+                        }
+                    }
+                }
+            }
+            hr {}
+            h6 { "Contents" }
+            small { "The first row matching the file's contents is sent when that changes." }
+            table {
+                thead {
+                    tr {
+                        th { "JSON Pointer" }
+                        th { "Pattern" }
+                        th { "Reports" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for (i, entry) in event_cfg.contents.iter().enumerate() {
+                        tr {
+                            td {
+                                input {
+                                    name: "pointer",
+                                    placeholder: "whole file",
+                                    value: "{entry.pointer.clone().unwrap_or_default()}",
+                                    oninput: move |e| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write()
+                                            && let Some(entry) = event_cfg.contents.get_mut(i)
+                                        {
+                                            entry.pointer = non_empty(e.value());
+                                        }
+                                    }
+                                }
+                            }
+                            td {
+                                input {
+                                    name: "pattern",
+                                    placeholder: "anything",
+                                    value: "{entry.pattern.clone().unwrap_or_default()}",
+                                    aria_invalid: entry
+                                        .pattern
+                                        .as_deref()
+                                        .is_some_and(|pattern| regex::Regex::new(pattern).is_err()),
+                                    oninput: move |e| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write()
+                                            && let Some(entry) = event_cfg.contents.get_mut(i)
+                                        {
+                                            entry.pattern = non_empty(e.value());
+                                        }
+                                    }
+                                }
+                            }
+                            td {
+                                ReportList {
+                                    reports: entry.reports.clone(),
+                                    on_change: move |reports| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write()
+                                            && let Some(entry) = event_cfg.contents.get_mut(i)
+                                        {
+                                            entry.reports = reports;
+                                        }
+                                    }
+                                }
+                            }
+                            td {
+                                button {
+                                    class: "danger",
+                                    onclick: move |_| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write()
+                                            && i < event_cfg.contents.len()
+                                        {
+                                            event_cfg.contents.remove(i);
+                                        }
+                                    },
+                                    "Delete"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            button {
+                class: "outline",
+                onclick: move |_| {
+                    let mut event_signal = props.event;
+                    if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.contents.push(config::FileContentsReports::default());
+                    }
+                },
+                "Add contents match"
+            }
+            h6 { "No Contents Match Reports" }
+            ReportList {
+                reports: event_cfg.no_contents_match_reports.clone(),
+                on_change: move |reports| {
+                    let mut event_signal = props.event;
+                    if let config::Event::FileWatch(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.no_contents_match_reports = reports;
+                    }
+                }
+            }
+        }
+    )
+}
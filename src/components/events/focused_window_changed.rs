@@ -6,6 +6,7 @@ use crate::{
     components::{
         dialog::Dialog,
         events::{edit_window::EditWindow, event_configurator::EventConfiguratorProps},
+        report_list::ReportList,
     },
     config, win,
 };
@@ -28,10 +29,6 @@ pub fn FocusedWindowChanged(props: EventConfiguratorProps) -> Element {
     let mut draft_window = use_signal(win::WindowMetadata::default);
     let mut edit_target = use_signal(|| None::<EditTarget>);
 
-    let mut show_report_editor = use_signal(|| false);
-    let mut draft_report = use_signal(Vec::<u8>::default);
-    let mut on_match = use_signal(|| true);
-
     let event_read = props.event.read();
     let config::Event::FocusedWindowChanged(event_cfg) = event_read.deref() else {
         panic!("Expected FocusedWindowChanged");
@@ -60,6 +57,12 @@ pub fn FocusedWindowChanged(props: EventConfiguratorProps) -> Element {
                                 if let Some(exe) = &window.exe {
                                     li { "{exe.to_string_lossy().to_string()}" },
                                 }
+                                if let Some(workspace) = &window.workspace {
+                                    li { "Workspace {workspace}" },
+                                }
+                                if let Some(is_fullscreen) = window.is_fullscreen {
+                                    li { if is_fullscreen { "fullscreen" } else { "windowed" } },
+                                }
                             },
                             div {
                                 role: "group",
@@ -127,6 +130,12 @@ pub fn FocusedWindowChanged(props: EventConfiguratorProps) -> Element {
                                 if let Some(exe) = &window.exe {
                                     li { "{exe.to_string_lossy().to_string()}" },
                                 }
+                                if let Some(workspace) = &window.workspace {
+                                    li { "Workspace {workspace}" },
+                                }
+                                if let Some(is_fullscreen) = window.is_fullscreen {
+                                    li { if is_fullscreen { "fullscreen" } else { "windowed" } },
+                                }
                             },
                             div {
                                 role: "group",
@@ -182,57 +191,27 @@ pub fn FocusedWindowChanged(props: EventConfiguratorProps) -> Element {
                 class: "grid",
                 div {
                     h6 { "On Match Reports" },
-                    for (i, report) in event_cfg.on_match_reports.iter().enumerate() {
-                        details {
-                            summary { "{hex::encode(report)}" }
-                            button {
-                                class: "danger",
-                                onclick: move |_| {
-                                    let mut event_signal = props.event;
-                                    let mut event = event_signal.write();
-                                    if let config::Event::FocusedWindowChanged(event_cfg) = &mut *event {
-                                        event_cfg.on_match_reports.remove(i);
-                                    }
-                                },
-                                "Delete"
+                    ReportList {
+                        reports: event_cfg.on_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::FocusedWindowChanged(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_match_reports = reports;
                             }
                         }
                     }
-                    button {
-                        class: "outline",
-                        onclick: move |_| {
-                            show_report_editor.set(true);
-                            on_match.set(true);
-                        },
-                        "Add"
-                    }
                 }
                 div {
                     h6 { "On No Match Reports" }
-                    for (i, report) in event_cfg.on_no_match_reports.iter().enumerate() {
-                        details {
-                            summary { "{hex::encode(report)}" }
-                            button {
-                                class: "danger",
-                                onclick: move |_| {
-                                    let mut event_signal = props.event;
-                                    let mut event = event_signal.write();
-                                    if let config::Event::FocusedWindowChanged(event_cfg) = &mut *event {
-                                        event_cfg.on_no_match_reports.remove(i);
-                                    }
-                                },
-                                "Delete"
+                    ReportList {
+                        reports: event_cfg.on_no_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::FocusedWindowChanged(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_no_match_reports = reports;
                             }
                         }
                     }
-                    button {
-                        class: "outline",
-                        onclick: move |_| {
-                            show_report_editor.set(true);
-                            on_match.set(false);
-                        },
-                        "Add"
-                    }
                 }
             }
         }
@@ -276,35 +255,6 @@ pub fn FocusedWindowChanged(props: EventConfiguratorProps) -> Element {
                     }
                 }
             }
-        },
-        if show_report_editor() {
-            Dialog {
-                title: "Report".to_string(),
-                on_cancel: move |_| show_report_editor.set(false),
-                on_ok: move |_| {
-                    let mut event_signal = props.event;
-                    let mut event = event_signal.write();
-                    if let config::Event::FocusedWindowChanged(event_cfg) = &mut *event {
-                        let report = std::mem::take(&mut *draft_report.write());
-                        if on_match() {
-                            event_cfg.on_match_reports.push(report);
-                        } else {
-                            event_cfg.on_no_match_reports.push(report);
-                        }
-                    }
-                    show_report_editor.set(false);
-                    draft_report.set(Vec::<u8>::default());
-                },
-                input {
-                    name: "report",
-                    value: hex::encode(draft_report()),
-                    oninput: move |e| {
-                        if let Ok(value) = hex::decode(e.value()) {
-                            draft_report.set(value);
-                        }
-                    }
-                }
-            }
         }
     )
 }
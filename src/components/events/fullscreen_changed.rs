@@ -0,0 +1,50 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+};
+
+#[component]
+pub fn FullscreenChanged(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::FullscreenChanged(event_cfg) = event_read.deref() else {
+        panic!("Expected FullscreenChanged");
+    };
+
+    rsx!(
+        div {
+            class: "event-config--fullscreen-changed",
+            small { "Sent when the focused window enters or leaves fullscreen, whatever the window." }
+            div {
+                class: "grid",
+                div {
+                    h6 { "On Fullscreen Reports" },
+                    ReportList {
+                        reports: event_cfg.on_fullscreen_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::FullscreenChanged(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_fullscreen_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "On Windowed Reports" }
+                    ReportList {
+                        reports: event_cfg.on_windowed_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::FullscreenChanged(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_windowed_reports = reports;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,53 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+};
+
+#[component]
+pub fn Interval(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::Interval(event_cfg) = event_read.deref() else {
+        panic!("Expected Interval");
+    };
+
+    rsx!(
+        div {
+            class: "event-config--interval",
+            label {
+                "Interval (seconds)",
+                input {
+                    type: "number",
+                    min: "1",
+                    value: "{event_cfg.interval_secs}",
+                    oninput: move |e| {
+                        let mut event_signal = props.event;
+                        if let Ok(interval_secs) = e.value().parse::<u64>()
+                            && let config::Event::Interval(event_cfg) = &mut *event_signal.write()
+                        {
+                            event_cfg.interval_secs = interval_secs.max(1);
+                        }
+                    }
+                }
+            }
+            small {
+                "Template reports can use {{cpu}}, {{ram}}, {{disk}}, {{net_rx}} and {{net_tx}}. "
+                "Append :FROM_MIN:FROM_MAX[:TO_MIN:TO_MAX] to scale the value, e.g. {{cpu:0:100:0:255}}."
+            }
+            hr {}
+            h6 { "Reports" }
+            ReportList {
+                reports: event_cfg.reports.clone(),
+                on_change: move |reports| {
+                    let mut event_signal = props.event;
+                    if let config::Event::Interval(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.reports = reports;
+                    }
+                }
+            }
+        }
+    )
+}
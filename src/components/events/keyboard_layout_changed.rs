@@ -0,0 +1,107 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config, layout,
+};
+
+#[component]
+pub fn KeyboardLayoutChanged(props: EventConfiguratorProps) -> Element {
+    let current_layout = use_signal(layout::get_layout);
+
+    let event_read = props.event.read();
+    let config::Event::KeyboardLayoutChanged(event_cfg) = event_read.deref() else {
+        panic!("Expected KeyboardLayoutChanged");
+    };
+
+    rsx!(
+        div {
+            class: "event-config--keyboard-layout-changed",
+            if let Some(current_layout) = current_layout() {
+                small { "Current layout: {current_layout}" }
+            }
+            table {
+                thead {
+                    tr {
+                        th { "Layout" }
+                        th { "Reports" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for (i, entry) in event_cfg.layouts.iter().enumerate() {
+                        tr {
+                            td {
+                                input {
+                                    name: "layout",
+                                    value: "{entry.layout}",
+                                    oninput: move |e| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::KeyboardLayoutChanged(event_cfg) = &mut *event_signal.write()
+                                            && let Some(entry) = event_cfg.layouts.get_mut(i)
+                                        {
+                                            entry.layout = e.value();
+                                        }
+                                    }
+                                }
+                            }
+                            td {
+                                ReportList {
+                                    reports: entry.reports.clone(),
+                                    on_change: move |reports| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::KeyboardLayoutChanged(event_cfg) = &mut *event_signal.write()
+                                            && let Some(entry) = event_cfg.layouts.get_mut(i)
+                                        {
+                                            entry.reports = reports;
+                                        }
+                                    }
+                                }
+                            }
+                            td {
+                                button {
+                                    class: "danger",
+                                    onclick: move |_| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::KeyboardLayoutChanged(event_cfg) = &mut *event_signal.write()
+                                            && i < event_cfg.layouts.len()
+                                        {
+                                            event_cfg.layouts.remove(i);
+                                        }
+                                    },
+                                    "Delete"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            button {
+                class: "outline",
+                onclick: move |_| {
+                    let mut event_signal = props.event;
+                    if let config::Event::KeyboardLayoutChanged(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.layouts.push(config::LayoutReports {
+                            layout: current_layout().unwrap_or_default(),
+                            reports: Vec::new(),
+                        });
+                    }
+                },
+                "Add layout"
+            }
+            hr {}
+            h6 { "Other Layout Reports" }
+            ReportList {
+                reports: event_cfg.other_reports.clone(),
+                on_change: move |reports| {
+                    let mut event_signal = props.event;
+                    if let config::Event::KeyboardLayoutChanged(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.other_reports = reports;
+                    }
+                }
+            }
+        }
+    )
+}
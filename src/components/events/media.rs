@@ -0,0 +1,129 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+    media::PlaybackStatus,
+};
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim().to_string();
+    if value.is_empty() { None } else { Some(value) }
+}
+
+#[component]
+pub fn Media(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::Media(event_cfg) = event_read.deref() else {
+        panic!("Expected Media");
+    };
+    let status = event_cfg.status;
+    let player = event_cfg.player.clone().unwrap_or_default();
+    let artist = event_cfg.artist.clone().unwrap_or_default();
+    let title = event_cfg.title.clone().unwrap_or_default();
+
+    rsx!(
+        div {
+            class: "event-config--media",
+            div {
+                class: "grid",
+                label {
+                    "Status",
+                    select {
+                        name: "status",
+                        aria_label: "Match on the playback status",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Media(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.status = e.value().parse::<PlaybackStatus>().ok();
+                            }
+                        },
+                        option { value: "any", selected: status.is_none(), "any" }
+                        for option_status in PlaybackStatus::iter() {
+                            option {
+                                selected: Some(option_status) == status,
+                                "{option_status}"
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Player",
+                    input {
+                        name: "player",
+                        placeholder: "spotify",
+                        value: "{player}",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Media(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.player = non_empty(e.value());
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "grid",
+                label {
+                    "Artist",
+                    input {
+                        name: "artist",
+                        placeholder: "any artist",
+                        value: "{artist}",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Media(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.artist = non_empty(e.value());
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Title",
+                    input {
+                        name: "title",
+                        placeholder: "any title",
+                        value: "{title}",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Media(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.title = non_empty(e.value());
+                            }
+                        }
+                    }
+                }
+            }
+            hr {}
+            div {
+                class: "grid",
+                div {
+                    h6 { "On Match Reports" },
+                    ReportList {
+                        reports: event_cfg.on_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Media(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_match_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "On No Match Reports" }
+                    ReportList {
+                        reports: event_cfg.on_no_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Media(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_no_match_reports = reports;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
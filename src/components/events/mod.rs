@@ -1,5 +1,16 @@
 pub mod capture_focused_window;
 pub mod capture_focused_window_shortcut;
+pub mod capture_in_use;
+pub mod clipboard;
 pub mod edit_window;
 pub mod event_configurator;
+pub mod file_watch;
 pub mod focused_window_changed;
+pub mod fullscreen_changed;
+pub mod interval;
+pub mod keyboard_layout_changed;
+pub mod media;
+pub mod network;
+pub mod power;
+pub mod webhook;
+pub mod workspace_changed;
@@ -0,0 +1,100 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config, net,
+};
+
+#[component]
+pub fn Network(props: EventConfiguratorProps) -> Element {
+    let interfaces = use_signal(net::get_interfaces);
+
+    let event_read = props.event.read();
+    let config::Event::Network(event_cfg) = event_read.deref() else {
+        panic!("Expected Network");
+    };
+    let state = event_cfg.state;
+
+    rsx!(
+        div {
+            class: "event-config--network",
+            div {
+                class: "grid",
+                label {
+                    "Interface",
+                    input {
+                        name: "interface",
+                        placeholder: "tun*",
+                        list: "network-interfaces",
+                        value: "{event_cfg.interface}",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Network(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.interface = e.value().trim().to_string();
+                            }
+                        }
+                    }
+                    datalist {
+                        id: "network-interfaces",
+                        for iface in interfaces.read().iter() {
+                            option { value: "{iface.name}" }
+                        }
+                    }
+                }
+                label {
+                    "State",
+                    select {
+                        name: "state",
+                        aria_label: "Select the interface state to match",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let Ok(state) = e.value().parse::<config::LinkState>()
+                                && let config::Event::Network(event_cfg) = &mut *event_signal.write()
+                            {
+                                event_cfg.state = state;
+                            }
+                        },
+                        for option_state in config::LinkState::iter() {
+                            option {
+                                selected: option_state == state,
+                                "{option_state}"
+                            }
+                        }
+                    }
+                }
+            }
+            small { "Use * and ? as wildcards, e.g. tun* or wg?. Reports are sent when the state changes." }
+            hr {}
+            div {
+                class: "grid",
+                div {
+                    h6 { "On Match Reports" },
+                    ReportList {
+                        reports: event_cfg.on_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Network(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_match_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "On No Match Reports" }
+                    ReportList {
+                        reports: event_cfg.on_no_match_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Network(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.on_no_match_reports = reports;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
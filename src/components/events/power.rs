@@ -0,0 +1,100 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+};
+
+#[component]
+pub fn Power(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::Power(event_cfg) = event_read.deref() else {
+        panic!("Expected Power");
+    };
+
+    rsx!(
+        div {
+            class: "event-config--power",
+            div {
+                class: "grid",
+                label {
+                    "Low battery (%)",
+                    input {
+                        type: "number",
+                        min: "0",
+                        max: "100",
+                        value: "{event_cfg.low_battery_percent}",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let Ok(percent) = e.value().parse::<u8>()
+                                && let config::Event::Power(event_cfg) = &mut *event_signal.write()
+                            {
+                                event_cfg.low_battery_percent = percent.min(100);
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Hysteresis (%)",
+                    input {
+                        type: "number",
+                        min: "0",
+                        max: "100",
+                        value: "{event_cfg.hysteresis_percent}",
+                        oninput: move |e| {
+                            let mut event_signal = props.event;
+                            if let Ok(percent) = e.value().parse::<u8>()
+                                && let config::Event::Power(event_cfg) = &mut *event_signal.write()
+                            {
+                                event_cfg.hysteresis_percent = percent.min(100);
+                            }
+                        }
+                    }
+                }
+            }
+            small { "Low battery ends once the charge rises above the threshold plus the hysteresis." }
+            hr {}
+            div {
+                class: "grid",
+                div {
+                    h6 { "On AC Reports" }
+                    ReportList {
+                        reports: event_cfg.ac_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Power(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.ac_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "On Battery Reports" }
+                    ReportList {
+                        reports: event_cfg.battery_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Power(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.battery_reports = reports;
+                            }
+                        }
+                    }
+                }
+                div {
+                    h6 { "Low Battery Reports" }
+                    ReportList {
+                        reports: event_cfg.low_battery_reports.clone(),
+                        on_change: move |reports| {
+                            let mut event_signal = props.event;
+                            if let config::Event::Power(event_cfg) = &mut *event_signal.write() {
+                                event_cfg.low_battery_reports = reports;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
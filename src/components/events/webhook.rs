@@ -0,0 +1,65 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{
+    CONFIG_SIGNAL,
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config,
+};
+
+#[component]
+pub fn Webhook(props: EventConfiguratorProps) -> Element {
+    let event_read = props.event.read();
+    let config::Event::Webhook(event_cfg) = event_read.deref() else {
+        panic!("Expected Webhook");
+    };
+    let port = CONFIG_SIGNAL
+        .read()
+        .webhook
+        .as_ref()
+        .map(|listener| listener.port);
+
+    rsx!(
+        div {
+            class: "event-config--webhook",
+            label {
+                "Name",
+                input {
+                    name: "name",
+                    placeholder: "build-finished",
+                    value: "{event_cfg.name}",
+                    oninput: move |e| {
+                        let mut event_signal = props.event;
+                        if let config::Event::Webhook(event_cfg) = &mut *event_signal.write() {
+                            event_cfg.name = e.value().trim().to_string();
+                        }
+                    }
+                }
+            }
+            match port {
+                Some(port) => rsx!(
+                    small { "POST http://127.0.0.1:{port}/webhook/{event_cfg.name} with Authorization: Bearer <token>." }
+                ),
+                None => rsx!(
+                    small { "The webhook listener is off, add a [webhook] table with a token to config.toml and restart." }
+                ),
+            }
+            small {
+                "Template reports can use fields of the JSON body, e.g. {{body:status:ascii:8}} as text "
+                "or {{body:/build/progress}} as a single byte."
+            }
+            hr {}
+            h6 { "Reports" }
+            ReportList {
+                reports: event_cfg.reports.clone(),
+                on_change: move |reports| {
+                    let mut event_signal = props.event;
+                    if let config::Event::Webhook(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.reports = reports;
+                    }
+                }
+            }
+        }
+    )
+}
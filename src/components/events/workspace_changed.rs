@@ -0,0 +1,107 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{
+    components::{events::event_configurator::EventConfiguratorProps, report_list::ReportList},
+    config, workspace,
+};
+
+#[component]
+pub fn WorkspaceChanged(props: EventConfiguratorProps) -> Element {
+    let current_workspace = use_signal(workspace::get_workspace);
+
+    let event_read = props.event.read();
+    let config::Event::WorkspaceChanged(event_cfg) = event_read.deref() else {
+        panic!("Expected WorkspaceChanged");
+    };
+
+    rsx!(
+        div {
+            class: "event-config--workspace-changed",
+            if let Some(current_workspace) = current_workspace() {
+                small { "Current workspace: {current_workspace}" }
+            }
+            table {
+                thead {
+                    tr {
+                        th { "Workspace" }
+                        th { "Reports" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for (i, entry) in event_cfg.workspaces.iter().enumerate() {
+                        tr {
+                            td {
+                                input {
+                                    name: "workspace",
+                                    value: "{entry.workspace}",
+                                    oninput: move |e| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::WorkspaceChanged(event_cfg) = &mut *event_signal.write()
+                                            && let Some(entry) = event_cfg.workspaces.get_mut(i)
+                                        {
+                                            entry.workspace = e.value();
+                                        }
+                                    }
+                                }
+                            }
+                            td {
+                                ReportList {
+                                    reports: entry.reports.clone(),
+                                    on_change: move |reports| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::WorkspaceChanged(event_cfg) = &mut *event_signal.write()
+                                            && let Some(entry) = event_cfg.workspaces.get_mut(i)
+                                        {
+                                            entry.reports = reports;
+                                        }
+                                    }
+                                }
+                            }
+                            td {
+                                button {
+                                    class: "danger",
+                                    onclick: move |_| {
+                                        let mut event_signal = props.event;
+                                        if let config::Event::WorkspaceChanged(event_cfg) = &mut *event_signal.write()
+                                            && i < event_cfg.workspaces.len()
+                                        {
+                                            event_cfg.workspaces.remove(i);
+                                        }
+                                    },
+                                    "Delete"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            button {
+                class: "outline",
+                onclick: move |_| {
+                    let mut event_signal = props.event;
+                    if let config::Event::WorkspaceChanged(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.workspaces.push(config::WorkspaceReports {
+                            workspace: current_workspace().unwrap_or_default(),
+                            reports: Vec::new(),
+                        });
+                    }
+                },
+                "Add workspace"
+            }
+            hr {}
+            h6 { "Other Workspace Reports" }
+            ReportList {
+                reports: event_cfg.other_reports.clone(),
+                on_change: move |reports| {
+                    let mut event_signal = props.event;
+                    if let config::Event::WorkspaceChanged(event_cfg) = &mut *event_signal.write() {
+                        event_cfg.other_reports = reports;
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,100 @@
+use dioxus::prelude::*;
+
+use crate::{
+    CONFIG_SIGNAL,
+    components::{dialog::Dialog, edit_group::EditGroup},
+    config,
+};
+
+#[derive(Debug, Copy, Clone)]
+struct EditTarget {
+    index: Option<usize>, // None = "Add", Some(i) = "Edit existing i"
+}
+
+// Manages named device groups that rules can target.
+#[component]
+pub fn Groups() -> Element {
+    let mut show_group_editor = use_signal(|| false);
+    let mut draft_group = use_signal(config::DeviceGroup::default);
+    let mut edit_target = use_signal(|| None::<EditTarget>);
+
+    rsx!(
+        div {
+            class: "groups",
+            for (i, group) in CONFIG_SIGNAL.read().groups.iter().enumerate() {
+                details {
+                    class: "group",
+                    summary { "{group.name}" }
+                    ul {
+                        for member in group.members.iter() {
+                            li { "{member.device}" }
+                        }
+                    }
+                    div {
+                        role: "group",
+                        class: "group__buttons",
+                        button {
+                            class: "outline",
+                            onclick: {
+                                let mut group = group.clone();
+                                move |_| {
+                                    edit_target.set(Some(EditTarget { index: Some(i) }));
+                                    draft_group.set(std::mem::take(&mut group));
+                                    show_group_editor.set(true);
+                                }
+                            },
+                            "Edit"
+                        },
+                        button {
+                            class: "danger",
+                            onclick: {
+                                let name = group.name.clone();
+                                move |_| {
+                                    CONFIG_SIGNAL.write().delete_group(&name);
+                                }
+                            },
+                            "Delete"
+                        }
+                    }
+                }
+            }
+            button {
+                class: "outline",
+                onclick: move |_| {
+                    draft_group.set(config::DeviceGroup::default());
+                    edit_target.set(Some(EditTarget { index: None }));
+                    show_group_editor.set(true);
+                },
+                "Add"
+            }
+        },
+        if show_group_editor() {
+            Dialog {
+                title: "Group".to_string(),
+                hide_buttons: true,
+                on_cancel: move |_| show_group_editor.set(false),
+                EditGroup {
+                    group: draft_group,
+                    on_submit: move || {
+                        let Some(target) = *edit_target.read() else {
+                            show_group_editor.set(false);
+                            return;
+                        };
+                        let mut new_group = std::mem::take(&mut *draft_group.write());
+                        let mut config = CONFIG_SIGNAL.write();
+                        new_group.name = config.unique_group_name(&new_group.name, target.index);
+                        if let Some(i) = target.index {
+                            let old_name = config.groups[i].name.clone();
+                            config.rename_group(&old_name, &new_group.name);
+                            config.groups[i] = new_group;
+                        } else {
+                            config.groups.push(new_group);
+                        }
+                        edit_target.set(None);
+                        show_group_editor.set(false);
+                    }
+                }
+            }
+        }
+    )
+}
@@ -19,7 +19,7 @@ pub fn HidDevices(props: HidDevicesProps) -> Element {
             .get_metadata_list()
     });
     let mut hid_device = use_signal(|| None::<hid::HidMetadata>);
-    let mut usage_pair = use_signal(|| None::<hid::UsagePair>);
+    let mut interface = use_signal(|| None::<hid::HidInterface>);
 
     rsx! {
         form {
@@ -31,23 +31,27 @@ pub fn HidDevices(props: HidDevicesProps) -> Element {
                     h6 { "PID: {device.product_id}" },
                     h6 { "VID: {device.vendor_id}" },
                     fieldset {
-                        class: "hid-device__usages",
-                        legend { "Usages" }
-                        for usage in device.usages.iter() {
+                        class: "hid-device__interfaces",
+                        legend { "Interfaces" }
+                        for hid_interface in device.interfaces.iter() {
                             label {
                                 input {
                                     type: "radio",
-                                    name: "usage",
+                                    name: "interface",
                                     onchange: {
                                         let mut device = device.clone();
-                                        let mut usage = usage.clone();
+                                        let mut hid_interface = hid_interface.clone();
                                         move |_| {
                                             hid_device.set(Some(std::mem::take(&mut device)));
-                                            usage_pair.set(Some(std::mem::take(&mut usage)));
+                                            interface.set(Some(std::mem::take(&mut hid_interface)));
                                         }
                                     }
                                 }
-                                "Usage Page: {usage.usage_page} - Usage: {usage.usage}"
+                                "Usage Page: {hid_interface.usage.usage_page} - Usage: {hid_interface.usage.usage} - Interface: {hid_interface.interface_number}"
+                                if let Some(serial_number) = &hid_interface.serial_number {
+                                    " - Serial: {serial_number}"
+                                }
+                                small { "{hid_interface.path}" }
                             }
                         }
                     }
@@ -57,7 +61,7 @@ pub fn HidDevices(props: HidDevicesProps) -> Element {
                 type: "submit",
                 onclick: move |_| {
                     if let Some(hid_device) = &*hid_device.read()
-                        && let Some(usage_pair) = &*usage_pair.read()
+                        && let Some(interface) = &*interface.read()
                     {
                         device.set(config::Device {
                             name: if !hid_device.product_string.is_empty() {
@@ -69,10 +73,15 @@ pub fn HidDevices(props: HidDevicesProps) -> Element {
                             },
                             vid: hid_device.vendor_id,
                             pid: hid_device.product_id,
-                            usage_page: usage_pair.usage_page,
-                            usage: usage_pair.usage,
+                            usage_page: interface.usage.usage_page,
+                            usage: interface.usage.usage,
                             report_length: u16::default(),
                             report_id: u8::default(),
+                            serial_number: interface.serial_number.clone(),
+                            interface_number: (interface.interface_number >= 0)
+                                .then_some(interface.interface_number),
+                            path: None,
+                            target: config::DeviceTarget::default(),
                         });
                     }
                     props.on_submit.call(());
@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use dioxus::prelude::*;
+use std::time::Duration;
+
+use crate::{
+    ATTACHED_TO_DAEMON, CONFIG_SIGNAL,
+    activity::Activity,
+    history::{self, HistoryEntry, HistoryFilter},
+    rpc,
+};
+
+// From the headless instance when attached to one, the embedded engine's
+// otherwise.
+async fn fetch_entries() -> Result<Vec<HistoryEntry>> {
+    if !*ATTACHED_TO_DAEMON.peek() {
+        return Ok(history::entries(&HistoryFilter::default()));
+    }
+    let entries =
+        tokio::task::spawn_blocking(|| rpc::call("history.list", serde_json::Value::Null))
+            .await
+            .context("history.list panicked")??;
+    serde_json::from_value(entries).context("invalid history.list response")
+}
+
+fn kind(activity: &Activity) -> &'static str {
+    match activity {
+        Activity::RuleEvaluated { .. } => "Evaluated",
+        Activity::RuleSent { .. } => "Sent",
+        Activity::ReportFailed { .. } => "Failed",
+        Activity::FocusedWindowChanged { .. } => "Focus",
+        Activity::Error { .. } => "Error",
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() { None } else { Some(value) }
+}
+
+// The recorded events, newest first, narrowed down to a rule or device.
+#[component]
+pub fn History() -> Element {
+    let mut entries: Signal<Vec<HistoryEntry>> = use_signal(Vec::new);
+    let mut filter = use_signal(HistoryFilter::default);
+    let mut export_message: Signal<Option<String>> = use_signal(|| None);
+
+    // Polled, the history is recorded outside of the UI.
+    use_future(move || async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+            match fetch_entries().await {
+                Ok(fetched) => {
+                    if *entries.peek() != fetched {
+                        entries.set(fetched);
+                    }
+                }
+                Err(e) => tracing::error!("history: {:#}", e),
+            }
+        }
+    });
+
+    let rule_names: Vec<String> = CONFIG_SIGNAL
+        .read()
+        .rules
+        .iter()
+        .map(|rule| rule.name.clone())
+        .collect();
+    let device_names: Vec<String> = CONFIG_SIGNAL
+        .read()
+        .devices
+        .iter()
+        .map(|device| device.name.clone())
+        .collect();
+
+    let current_filter = filter();
+    let filtered: Vec<HistoryEntry> = entries
+        .read()
+        .iter()
+        .filter(|entry| current_filter.matches(entry))
+        .cloned()
+        .collect();
+
+    // Exports what is shown, next to config.toml.
+    let export = {
+        let filtered = filtered.clone();
+        move |_| {
+            let file_name = format!(
+                "history-{}.jsonl",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            );
+            let message = std::env::current_dir()
+                .context("Failed to get current directory")
+                .map(|dir| dir.join(file_name))
+                .and_then(|path| {
+                    history::export(&filtered, &path)?;
+                    Ok(format!("Exported to {}", path.display()))
+                })
+                .unwrap_or_else(|e| format!("{e:#}"));
+            export_message.set(Some(message));
+        }
+    };
+
+    rsx!(
+        div {
+            class: "history",
+            div {
+                class: "grid",
+                select {
+                    aria_label: "Filter by rule",
+                    oninput: move |e| filter.write().rule = non_empty(e.value()),
+                    option { value: "", "All rules" }
+                    for name in rule_names {
+                        option {
+                            selected: current_filter.rule.as_ref() == Some(&name),
+                            value: "{name}",
+                            "{name}"
+                        }
+                    }
+                }
+                select {
+                    aria_label: "Filter by device",
+                    oninput: move |e| filter.write().device = non_empty(e.value()),
+                    option { value: "", "All devices" }
+                    for name in device_names {
+                        option {
+                            selected: current_filter.device.as_ref() == Some(&name),
+                            value: "{name}",
+                            "{name}"
+                        }
+                    }
+                }
+                button {
+                    class: "outline",
+                    onclick: export,
+                    "Export JSON lines"
+                }
+            }
+            if let Some(message) = export_message() {
+                small { "{message}" }
+            }
+            if filtered.is_empty() {
+                small { "Nothing recorded yet." }
+            }
+            table {
+                tbody {
+                    for entry in filtered.into_iter().rev() {
+                        tr {
+                            td {
+                                title: "{entry.time}",
+                                // Just the time of day, e.g. 12:34:56.789.
+                                "{entry.time.get(11..23).unwrap_or(&entry.time)}"
+                            }
+                            td { "{kind(&entry.activity)}" }
+                            td { "{entry.activity}" }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
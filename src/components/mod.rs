@@ -1,7 +1,16 @@
 pub mod devices;
 pub mod dialog;
 pub mod edit_device;
+pub mod edit_group;
+pub mod edit_report;
+pub mod edit_report_definition;
 pub mod edit_rule;
 pub mod events;
+pub mod groups;
 pub mod hid_devices;
+pub mod history;
+pub mod report_library;
+pub mod report_list;
 pub mod rules;
+pub mod select_devices;
+pub mod test_rule;
@@ -0,0 +1,104 @@
+use dioxus::prelude::*;
+
+use crate::{
+    CONFIG_SIGNAL,
+    components::{dialog::Dialog, edit_report_definition::EditReportDefinition},
+    config,
+};
+
+#[derive(Debug, Copy, Clone)]
+struct EditTarget {
+    index: Option<usize>, // None = "Add", Some(i) = "Edit existing i"
+}
+
+// Manages the named reports that rules reference instead of raw bytes.
+#[component]
+pub fn ReportLibrary() -> Element {
+    let mut show_definition_editor = use_signal(|| false);
+    let mut draft_definition = use_signal(config::ReportDefinition::default);
+    let mut edit_target = use_signal(|| None::<EditTarget>);
+
+    rsx!(
+        div {
+            class: "report-library",
+            for (i, definition) in CONFIG_SIGNAL.read().reports.iter().enumerate() {
+                details {
+                    class: "report-definition",
+                    summary { "{definition.name}" }
+                    if !definition.description.is_empty() {
+                        p { "{definition.description}" }
+                    }
+                    ol {
+                        for report in definition.reports.iter() {
+                            li { "{report}" }
+                        }
+                    }
+                    div {
+                        role: "group",
+                        class: "report-definition__buttons",
+                        button {
+                            class: "outline",
+                            onclick: {
+                                let mut definition = definition.clone();
+                                move |_| {
+                                    edit_target.set(Some(EditTarget { index: Some(i) }));
+                                    draft_definition.set(std::mem::take(&mut definition));
+                                    show_definition_editor.set(true);
+                                }
+                            },
+                            "Edit"
+                        },
+                        button {
+                            class: "danger",
+                            onclick: {
+                                let name = definition.name.clone();
+                                move |_| {
+                                    CONFIG_SIGNAL.write().delete_report(&name);
+                                }
+                            },
+                            "Delete"
+                        }
+                    }
+                }
+            }
+            button {
+                class: "outline",
+                onclick: move |_| {
+                    draft_definition.set(config::ReportDefinition::default());
+                    edit_target.set(Some(EditTarget { index: None }));
+                    show_definition_editor.set(true);
+                },
+                "Add"
+            }
+        },
+        if show_definition_editor() {
+            Dialog {
+                title: "Report".to_string(),
+                hide_buttons: true,
+                on_cancel: move |_| show_definition_editor.set(false),
+                EditReportDefinition {
+                    definition: draft_definition,
+                    on_submit: move || {
+                        let Some(target) = *edit_target.read() else {
+                            show_definition_editor.set(false);
+                            return;
+                        };
+                        let mut new_definition = std::mem::take(&mut *draft_definition.write());
+                        let mut config = CONFIG_SIGNAL.write();
+                        new_definition.name =
+                            config.unique_report_name(&new_definition.name, target.index);
+                        if let Some(i) = target.index {
+                            let old_name = config.reports[i].name.clone();
+                            config.rename_report(&old_name, &new_definition.name);
+                            config.reports[i] = new_definition;
+                        } else {
+                            config.reports.push(new_definition);
+                        }
+                        edit_target.set(None);
+                        show_definition_editor.set(false);
+                    }
+                }
+            }
+        }
+    )
+}
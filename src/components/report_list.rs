@@ -0,0 +1,68 @@
+use dioxus::prelude::*;
+
+use crate::{
+    components::{dialog::Dialog, edit_report::EditReport},
+    config,
+};
+
+#[derive(Props, PartialEq, Clone)]
+pub struct ReportListProps {
+    pub reports: Vec<config::Report>,
+    pub on_change: EventHandler<Vec<config::Report>>,
+}
+
+#[component]
+pub fn ReportList(props: ReportListProps) -> Element {
+    let mut show_report_editor = use_signal(|| false);
+    let mut draft_report = use_signal(config::Report::default);
+
+    rsx!(
+        div {
+            class: "report-list",
+            for (i, report) in props.reports.iter().enumerate() {
+                details {
+                    summary { "{report}" }
+                    button {
+                        class: "danger",
+                        onclick: {
+                            let mut reports = props.reports.clone();
+                            move |_| {
+                                if i < reports.len() {
+                                    reports.remove(i);
+                                }
+                                props.on_change.call(std::mem::take(&mut reports));
+                            }
+                        },
+                        "Delete"
+                    }
+                }
+            }
+            button {
+                class: "outline",
+                onclick: move |_| {
+                    draft_report.set(config::Report::default());
+                    show_report_editor.set(true);
+                },
+                "Add"
+            }
+        }
+        if show_report_editor() {
+            Dialog {
+                title: "Report".to_string(),
+                on_cancel: move |_| show_report_editor.set(false),
+                on_ok: {
+                    let reports = props.reports.clone();
+                    move |_| {
+                        let mut reports = reports.clone();
+                        reports.push(std::mem::take(&mut *draft_report.write()));
+                        props.on_change.call(reports);
+                        show_report_editor.set(false);
+                    }
+                },
+                EditReport {
+                    report: draft_report,
+                }
+            }
+        }
+    )
+}
@@ -9,11 +9,11 @@ pub struct RulesProps {
 
 #[component]
 pub fn Rules(props: RulesProps) -> Element {
-    let rule_names: Vec<String> = CONFIG_SIGNAL
+    let rules: Vec<(String, bool)> = CONFIG_SIGNAL
         .read()
         .rules
         .iter()
-        .map(|r| r.name.clone())
+        .map(|r| (r.name.clone(), !r.disabled))
         .collect();
 
     rsx! {
@@ -25,10 +25,25 @@ pub fn Rules(props: RulesProps) -> Element {
             }
             div {
                 class: "rules__list",
-                for name in rule_names {
+                for (name, enabled) in rules {
                     article {
                         class: "rules__item",
-                        "{name}"
+                        label {
+                            input {
+                                type: "checkbox",
+                                role: "switch",
+                                checked: enabled,
+                                onchange: {
+                                    let name = name.clone();
+                                    move |e: FormEvent| {
+                                        if let Some(rule) = CONFIG_SIGNAL.write().get_mut_rule(&name) {
+                                            rule.disabled = !e.checked();
+                                        }
+                                    }
+                                }
+                            }
+                            "{name}"
+                        }
                         div {
                             role: "group",
                             class: "buttons",
@@ -0,0 +1,91 @@
+use dioxus::prelude::*;
+
+use crate::CONFIG_SIGNAL;
+
+#[derive(Props, PartialEq, Clone)]
+pub struct SelectDevicesProps {
+    pub devices: Signal<Vec<String>>,
+    pub groups: Signal<Vec<String>>,
+}
+
+// Picks which registered devices and groups a rule sends its reports to.
+#[component]
+pub fn SelectDevices(props: SelectDevicesProps) -> Element {
+    let mut selected = props.devices;
+    let mut selected_groups = props.groups;
+
+    let names: Vec<String> = CONFIG_SIGNAL
+        .read()
+        .devices
+        .iter()
+        .map(|d| d.name.clone())
+        .collect();
+
+    let group_names: Vec<String> = CONFIG_SIGNAL
+        .read()
+        .groups
+        .iter()
+        .map(|g| g.name.clone())
+        .collect();
+
+    rsx!(
+        fieldset {
+            class: "select-devices",
+            if names.is_empty() {
+                small { "No devices registered, add some from the Devices screen." }
+            }
+            for name in names {
+                label {
+                    input {
+                        type: "checkbox",
+                        name: "devices",
+                        checked: selected.read().contains(&name),
+                        onchange: {
+                            let name = name.clone();
+                            move |e: FormEvent| {
+                                let mut selected = selected.write();
+                                if e.checked() {
+                                    if !selected.contains(&name) {
+                                        selected.push(name.clone());
+                                    }
+                                } else {
+                                    selected.retain(|n| *n != name);
+                                }
+                            }
+                        }
+                    }
+                    "{name}"
+                }
+            }
+        }
+        if !group_names.is_empty() {
+            fieldset {
+                class: "select-devices",
+                legend { "Groups" }
+                for name in group_names {
+                    label {
+                        input {
+                            type: "checkbox",
+                            name: "groups",
+                            checked: selected_groups.read().contains(&name),
+                            onchange: {
+                                let name = name.clone();
+                                move |e: FormEvent| {
+                                    let mut selected_groups = selected_groups.write();
+                                    if e.checked() {
+                                        if !selected_groups.contains(&name) {
+                                            selected_groups.push(name.clone());
+                                        }
+                                    } else {
+                                        selected_groups.retain(|n| *n != name);
+                                    }
+                                }
+                            }
+                        }
+                        "{name}"
+                    }
+                }
+            }
+        }
+    )
+}
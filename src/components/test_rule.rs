@@ -0,0 +1,169 @@
+use dioxus::prelude::*;
+use std::path::PathBuf;
+
+use crate::{
+    CONFIG_SIGNAL, FOCUSED_WINDOW_SIGNAL, config,
+    simulate::{self, DeviceSimulation},
+    template::TemplateContext,
+};
+
+#[derive(Props, PartialEq, Clone)]
+pub struct TestRuleProps {
+    pub name: String,
+    pub event: Signal<config::Event>,
+    pub devices: Signal<Vec<String>>,
+    pub groups: Signal<Vec<String>>,
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.trim().is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[component]
+fn SimulationTable(devices: Vec<DeviceSimulation>) -> Element {
+    rsx!(
+        if devices.is_empty() {
+            small { "No devices to send to." }
+        }
+        table {
+            tbody {
+                for simulation in devices {
+                    tr {
+                        td {
+                            "{simulation.device}"
+                            if !simulation.connected {
+                                small { " (not connected)" }
+                            }
+                        }
+                        td {
+                            if simulation.actions.is_empty() {
+                                small { "nothing" }
+                            }
+                            for action in simulation.actions {
+                                div { code { "{action}" } }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+// Previews what the rule being edited would write to each device, for a
+// window or a recorded event log, without writing anything.
+#[component]
+pub fn TestRule(props: TestRuleProps) -> Element {
+    let mut window = use_signal(|| FOCUSED_WINDOW_SIGNAL.peek().clone());
+    let mut log = use_signal(String::new);
+
+    let rule = config::Rule {
+        name: props.name.clone(),
+        event: props.event.read().clone(),
+        devices: props.devices.read().clone(),
+        groups: props.groups.read().clone(),
+        disabled: false,
+    };
+    let config = CONFIG_SIGNAL.read();
+
+    if !simulate::reacts_to_windows(&rule) {
+        let context = TemplateContext::current();
+        let previews: Vec<Vec<DeviceSimulation>> = rule
+            .event
+            .report_lists()
+            .into_iter()
+            .map(|reports| simulate::simulate_reports(&config, &rule, reports, &context))
+            .collect();
+
+        return rsx!(
+            div {
+                class: "test-rule",
+                small { "Each of the event's report lists, in order, with templates rendered from now." }
+                for (i, devices) in previews.into_iter().enumerate() {
+                    h6 { "Report list {i + 1}" }
+                    SimulationTable { devices }
+                }
+            }
+        );
+    }
+
+    let windows = if log.read().trim().is_empty() {
+        Ok(vec![window()])
+    } else {
+        simulate::parse_window_log(&log.read())
+    };
+    let (simulations, log_error) = match windows {
+        Ok(windows) => (simulate::simulate_windows(&config, &rule, &windows), None),
+        Err(e) => (Vec::new(), Some(format!("{e:#}"))),
+    };
+    let current = window();
+
+    rsx!(
+        div {
+            class: "test-rule",
+            div {
+                class: "grid",
+                label {
+                    "Title",
+                    input {
+                        value: "{current.title.clone().unwrap_or_default()}",
+                        oninput: move |e| window.write().title = non_empty(e.value()),
+                    }
+                }
+                label {
+                    "Class",
+                    input {
+                        value: "{current.class.clone().unwrap_or_default()}",
+                        oninput: move |e| window.write().class = non_empty(e.value()),
+                    }
+                }
+                label {
+                    "Exe",
+                    input {
+                        value: "{current.exe.clone().unwrap_or_default().display()}",
+                        oninput: move |e| window.write().exe = non_empty(e.value()).map(PathBuf::from),
+                    }
+                }
+            }
+            label {
+                input {
+                    type: "checkbox",
+                    checked: current.is_fullscreen.unwrap_or(false),
+                    onchange: move |e: FormEvent| window.write().is_fullscreen = Some(e.checked()),
+                }
+                "Fullscreen"
+            }
+            button {
+                class: "outline",
+                onclick: move |e| {
+                    e.prevent_default();
+                    window.set(FOCUSED_WINDOW_SIGNAL.read().clone());
+                },
+                "Use focused window"
+            }
+            label {
+                "Event log",
+                textarea {
+                    placeholder: "JSON lines from the control socket's event stream or an exported history, instead of the window above",
+                    value: "{log}",
+                    aria_invalid: log_error.is_some(),
+                    oninput: move |e| log.set(e.value()),
+                }
+                if let Some(error) = &log_error {
+                    small { "{error}" }
+                }
+            }
+            if simulations.is_empty() && log_error.is_none() {
+                small { "The rule sends nothing." }
+            }
+            for simulation in simulations {
+                h6 { "{simulation.window.title.clone().unwrap_or_default()}" }
+                SimulationTable { devices: simulation.devices }
+            }
+        }
+    )
+}
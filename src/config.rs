@@ -8,9 +8,23 @@ use std::{
     env, fs,
     io::Write,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
-use crate::win::WindowMetadata;
+use crate::{
+    activity::{self, Activity},
+    capture::CaptureDevice,
+    clipboard::{ClipboardContent, ClipboardContentType},
+    filewatch::{self, FileChange},
+    logging,
+    media::{MediaState, PlaybackStatus},
+    net::{self, NetInterface},
+    power::{PowerState, PowerStatus},
+    qmk,
+    sequencer::{self, ReportAction},
+    template::Template,
+    win::WindowMetadata,
+};
 
 const CONFIG_PATH: &str = "config.toml";
 
@@ -27,20 +41,916 @@ const CONFIG_PATH: &str = "config.toml";
 #[strum(serialize_all = "snake_case")]
 pub enum Event {
     FocusedWindowChanged(FocusedWindowChangedConfig),
+    Interval(IntervalConfig),
+    Power(PowerConfig),
+    Network(NetworkConfig),
+    KeyboardLayoutChanged(KeyboardLayoutChangedConfig),
+    FullscreenChanged(FullscreenChangedConfig),
+    WorkspaceChanged(WorkspaceChangedConfig),
+    CaptureInUse(CaptureInUseConfig),
+    Media(MediaConfig),
+    Clipboard(ClipboardConfig),
+    FileWatch(FileWatchConfig),
+    Webhook(WebhookConfig),
+}
+
+impl Default for Event {
+    fn default() -> Self {
+        Self::FocusedWindowChanged(FocusedWindowChangedConfig::default())
+    }
+}
+
+impl Event {
+    pub fn report_lists_mut(&mut self) -> Vec<&mut Vec<Report>> {
+        match self {
+            Self::FocusedWindowChanged(event_cfg) => vec![
+                &mut event_cfg.on_match_reports,
+                &mut event_cfg.on_no_match_reports,
+            ],
+            Self::Interval(event_cfg) => vec![&mut event_cfg.reports],
+            Self::Power(event_cfg) => vec![
+                &mut event_cfg.ac_reports,
+                &mut event_cfg.battery_reports,
+                &mut event_cfg.low_battery_reports,
+            ],
+            Self::Network(event_cfg) => vec![
+                &mut event_cfg.on_match_reports,
+                &mut event_cfg.on_no_match_reports,
+            ],
+            Self::KeyboardLayoutChanged(event_cfg) => event_cfg
+                .layouts
+                .iter_mut()
+                .map(|layout| &mut layout.reports)
+                .chain([&mut event_cfg.other_reports])
+                .collect(),
+            Self::FullscreenChanged(event_cfg) => vec![
+                &mut event_cfg.on_fullscreen_reports,
+                &mut event_cfg.on_windowed_reports,
+            ],
+            Self::WorkspaceChanged(event_cfg) => event_cfg
+                .workspaces
+                .iter_mut()
+                .map(|workspace| &mut workspace.reports)
+                .chain([&mut event_cfg.other_reports])
+                .collect(),
+            Self::CaptureInUse(event_cfg) => {
+                vec![&mut event_cfg.in_use_reports, &mut event_cfg.idle_reports]
+            }
+            Self::Media(event_cfg) => vec![
+                &mut event_cfg.on_match_reports,
+                &mut event_cfg.on_no_match_reports,
+            ],
+            Self::Clipboard(event_cfg) => vec![
+                &mut event_cfg.on_match_reports,
+                &mut event_cfg.on_no_match_reports,
+            ],
+            Self::FileWatch(event_cfg) => [
+                &mut event_cfg.on_create_reports,
+                &mut event_cfg.on_modify_reports,
+                &mut event_cfg.on_delete_reports,
+            ]
+            .into_iter()
+            .chain(
+                event_cfg
+                    .contents
+                    .iter_mut()
+                    .map(|contents| &mut contents.reports),
+            )
+            .chain([&mut event_cfg.no_contents_match_reports])
+            .collect(),
+            Self::Webhook(event_cfg) => vec![&mut event_cfg.reports],
+        }
+    }
+
+    pub fn report_lists(&self) -> Vec<&Vec<Report>> {
+        match self {
+            Self::FocusedWindowChanged(event_cfg) => {
+                vec![&event_cfg.on_match_reports, &event_cfg.on_no_match_reports]
+            }
+            Self::Interval(event_cfg) => vec![&event_cfg.reports],
+            Self::Power(event_cfg) => vec![
+                &event_cfg.ac_reports,
+                &event_cfg.battery_reports,
+                &event_cfg.low_battery_reports,
+            ],
+            Self::Network(event_cfg) => {
+                vec![&event_cfg.on_match_reports, &event_cfg.on_no_match_reports]
+            }
+            Self::KeyboardLayoutChanged(event_cfg) => event_cfg
+                .layouts
+                .iter()
+                .map(|layout| &layout.reports)
+                .chain([&event_cfg.other_reports])
+                .collect(),
+            Self::FullscreenChanged(event_cfg) => vec![
+                &event_cfg.on_fullscreen_reports,
+                &event_cfg.on_windowed_reports,
+            ],
+            Self::WorkspaceChanged(event_cfg) => event_cfg
+                .workspaces
+                .iter()
+                .map(|workspace| &workspace.reports)
+                .chain([&event_cfg.other_reports])
+                .collect(),
+            Self::CaptureInUse(event_cfg) => {
+                vec![&event_cfg.in_use_reports, &event_cfg.idle_reports]
+            }
+            Self::Media(event_cfg) => {
+                vec![&event_cfg.on_match_reports, &event_cfg.on_no_match_reports]
+            }
+            Self::Clipboard(event_cfg) => {
+                vec![&event_cfg.on_match_reports, &event_cfg.on_no_match_reports]
+            }
+            Self::FileWatch(event_cfg) => [
+                &event_cfg.on_create_reports,
+                &event_cfg.on_modify_reports,
+                &event_cfg.on_delete_reports,
+            ]
+            .into_iter()
+            .chain(event_cfg.contents.iter().map(|contents| &contents.reports))
+            .chain([&event_cfg.no_contents_match_reports])
+            .collect(),
+            Self::Webhook(event_cfg) => vec![&event_cfg.reports],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Report {
+    Bytes(Vec<u8>),
+    // Name of a definition in the config's report library.
+    Named(String),
+    Qmk(qmk::Preset),
+    Step(ReportStep),
+    Template(TemplateReport),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateReport {
+    pub template: Template,
+}
+
+fn default_repeat() -> u32 {
+    1
+}
+
+// Sends a report, then waits, `repeat` times. Without `send` it is only a
+// pause, with `device` it only applies to that device.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportStep {
+    pub send: Option<Box<Report>>,
+    #[serde(default)]
+    pub wait_ms: u64,
+    #[serde(default = "default_repeat")]
+    pub repeat: u32,
+    pub device: Option<String>,
+}
+
+impl Default for ReportStep {
+    fn default() -> Self {
+        Self {
+            send: None,
+            wait_ms: 0,
+            repeat: default_repeat(),
+            device: None,
+        }
+    }
+}
+
+impl Default for Report {
+    fn default() -> Self {
+        Self::Bytes(Vec::new())
+    }
+}
+
+// Library definitions may reference each other, this guards against cycles.
+const MAX_REPORT_NESTING: usize = 8;
+
+impl Report {
+    // What to do to send this report to the device, presets and library
+    // references may expand to several reports.
+    pub fn actions(&self, config: &Config, device: &Device) -> Vec<ReportAction> {
+        self.actions_nested(config, device, 0)
+    }
+
+    fn actions_nested(&self, config: &Config, device: &Device, depth: usize) -> Vec<ReportAction> {
+        match self {
+            Self::Bytes(bytes) => vec![ReportAction::Send(bytes.clone())],
+            Self::Template(report) => vec![ReportAction::SendTemplate(report.template.clone())],
+            Self::Qmk(preset) => preset
+                .encode()
+                .into_iter()
+                .map(ReportAction::Send)
+                .collect(),
+            Self::Named(name) => {
+                if depth >= MAX_REPORT_NESTING {
+                    tracing::error!(
+                        "Report {name} is nested too deeply, does it reference itself?"
+                    );
+                    return Vec::new();
+                }
+                let Some(definition) = config.get_report(name) else {
+                    tracing::error!("Unknown report {name}");
+                    return Vec::new();
+                };
+                definition
+                    .reports
+                    .iter()
+                    .flat_map(|report| report.actions_nested(config, device, depth + 1))
+                    .collect()
+            }
+            Self::Step(step) => {
+                if let Some(step_device) = &step.device
+                    && *step_device != device.name
+                {
+                    return Vec::new();
+                }
+
+                let send = step
+                    .send
+                    .as_ref()
+                    .map(|report| report.actions_nested(config, device, depth + 1))
+                    .unwrap_or_default();
+
+                let mut actions = Vec::new();
+                for _ in 0..step.repeat {
+                    actions.extend(send.iter().cloned());
+                    if step.wait_ms > 0 {
+                        actions.push(ReportAction::Wait(Duration::from_millis(step.wait_ms)));
+                    }
+                }
+                actions
+            }
+        }
+    }
+
+    pub fn references_report(&self, name: &str) -> bool {
+        match self {
+            Self::Named(n) => n == name,
+            Self::Step(step) => step
+                .send
+                .as_ref()
+                .is_some_and(|report| report.references_report(name)),
+            Self::Bytes(_) | Self::Qmk(_) | Self::Template(_) => false,
+        }
+    }
+
+    // Whether a step, or one it sends, only applies to the device.
+    pub fn references_device(&self, name: &str) -> bool {
+        match self {
+            Self::Step(step) => {
+                step.device.as_deref() == Some(name)
+                    || step
+                        .send
+                        .as_ref()
+                        .is_some_and(|report| report.references_device(name))
+            }
+            Self::Bytes(_) | Self::Named(_) | Self::Qmk(_) | Self::Template(_) => false,
+        }
+    }
+
+    pub fn rename_report_reference(&mut self, old_name: &str, new_name: &str) {
+        match self {
+            Self::Named(name) if name == old_name => *name = new_name.to_string(),
+            Self::Step(step) => {
+                if let Some(report) = step.send.as_mut() {
+                    report.rename_report_reference(old_name, new_name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn rename_device_reference(&mut self, old_name: &str, new_name: &str) {
+        if let Self::Step(step) = self {
+            if step.device.as_deref() == Some(old_name) {
+                step.device = Some(new_name.to_string());
+            }
+            if let Some(report) = step.send.as_mut() {
+                report.rename_device_reference(old_name, new_name);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bytes(bytes) => write!(f, "{}", hex::encode(bytes)),
+            Self::Named(name) => write!(f, "{name}"),
+            Self::Template(report) => write!(f, "{}", report.template),
+            Self::Qmk(preset) => write!(f, "{}", preset.describe()),
+            Self::Step(step) => {
+                match &step.send {
+                    Some(report) => write!(f, "Send {report}")?,
+                    None => write!(f, "Pause")?,
+                }
+                if step.repeat != 1 {
+                    write!(f, " x{}", step.repeat)?;
+                }
+                if step.wait_ms > 0 {
+                    write!(f, ", wait {} ms", step.wait_ms)?;
+                }
+                if let Some(device) = &step.device {
+                    write!(f, ", {device} only")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// A named report, or ordered sequence of reports, that rules can reference.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ReportDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub reports: Vec<Report>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FocusedWindowChangedConfig {
+    pub inclusions: Vec<WindowMetadata>,
+    pub exclusions: Vec<WindowMetadata>,
+    pub on_match_reports: Vec<Report>,
+    pub on_no_match_reports: Vec<Report>,
+}
+
+impl FocusedWindowChangedConfig {
+    // Matches when any inclusion matches the window and no exclusion does.
+    pub fn is_match(&self, window: &WindowMetadata) -> bool {
+        let exclusion_found = self
+            .exclusions
+            .iter()
+            .any(|exclusion| window.match_any(exclusion));
+
+        let inclusion_found = self
+            .inclusions
+            .iter()
+            .any(|inclusion| window.match_any(inclusion));
+
+        inclusion_found && !exclusion_found
+    }
+
+    pub fn reports(&self, is_match: bool) -> &[Report] {
+        if is_match {
+            &self.on_match_reports
+        } else {
+            &self.on_no_match_reports
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DeviceTarget {
+    // Only the first connected interface that matches.
+    #[default]
+    First,
+    // Every connected interface that matches, e.g. several identical macropads.
+    All,
+}
+
+fn default_interval_secs() -> u64 {
+    5
+}
+
+// Sends the reports every `interval_secs`, e.g. system metrics rendered with
+// report templates. Paused while none of the rule's devices are connected.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IntervalConfig {
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    pub reports: Vec<Report>,
+}
+
+impl Default for IntervalConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_interval_secs(),
+            reports: Vec::new(),
+        }
+    }
+}
+
+fn default_low_battery_percent() -> u8 {
+    20
+}
+
+fn default_hysteresis_percent() -> u8 {
+    5
+}
+
+// Sends reports when the machine switches between AC, battery and low battery.
+// Low battery is left again only once the charge rises `hysteresis_percent`
+// above the threshold, so a charge hovering around it doesn't flap.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PowerConfig {
+    #[serde(default = "default_low_battery_percent")]
+    pub low_battery_percent: u8,
+    #[serde(default = "default_hysteresis_percent")]
+    pub hysteresis_percent: u8,
+    #[serde(default)]
+    pub ac_reports: Vec<Report>,
+    #[serde(default)]
+    pub battery_reports: Vec<Report>,
+    #[serde(default)]
+    pub low_battery_reports: Vec<Report>,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            low_battery_percent: default_low_battery_percent(),
+            hysteresis_percent: default_hysteresis_percent(),
+            ac_reports: Vec::new(),
+            battery_reports: Vec::new(),
+            low_battery_reports: Vec::new(),
+        }
+    }
+}
+
+impl PowerConfig {
+    pub fn state(&self, status: &PowerStatus, previous: Option<PowerState>) -> PowerState {
+        if status.on_ac {
+            return PowerState::Ac;
+        }
+
+        let Some(percent) = status.battery_percent else {
+            return PowerState::Battery;
+        };
+
+        let threshold = if previous == Some(PowerState::LowBattery) {
+            self.low_battery_percent
+                .saturating_add(self.hysteresis_percent)
+        } else {
+            self.low_battery_percent
+        };
+
+        if percent <= threshold {
+            PowerState::LowBattery
+        } else {
+            PowerState::Battery
+        }
+    }
+
+    pub fn reports(&self, state: PowerState) -> &[Report] {
+        match state {
+            PowerState::Ac => &self.ac_reports,
+            PowerState::Battery => &self.battery_reports,
+            PowerState::LowBattery => &self.low_battery_reports,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LinkState {
+    #[default]
+    Up,
+    Down,
+}
+
+// Matches when an interface whose name matches `interface` (`*` and `?`
+// wildcards) is up, or with `state = "down"` when none of them are.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct NetworkConfig {
+    pub interface: String,
+    #[serde(default)]
+    pub state: LinkState,
+    #[serde(default)]
+    pub on_match_reports: Vec<Report>,
+    #[serde(default)]
+    pub on_no_match_reports: Vec<Report>,
+}
+
+impl NetworkConfig {
+    pub fn is_match(&self, interfaces: &[NetInterface]) -> bool {
+        let any_up = interfaces
+            .iter()
+            .any(|iface| iface.up && net::matches_pattern(&self.interface, &iface.name));
+
+        match self.state {
+            LinkState::Up => any_up,
+            LinkState::Down => !any_up,
+        }
+    }
+
+    pub fn reports(&self, is_match: bool) -> &[Report] {
+        if is_match {
+            &self.on_match_reports
+        } else {
+            &self.on_no_match_reports
+        }
+    }
+}
+
+// Sends reports when the focused window enters or leaves fullscreen, whatever
+// the window.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FullscreenChangedConfig {
+    #[serde(default)]
+    pub on_fullscreen_reports: Vec<Report>,
+    #[serde(default)]
+    pub on_windowed_reports: Vec<Report>,
+}
+
+impl FullscreenChangedConfig {
+    pub fn reports(&self, is_fullscreen: bool) -> &[Report] {
+        if is_fullscreen {
+            &self.on_fullscreen_reports
+        } else {
+            &self.on_windowed_reports
+        }
+    }
+}
+
+// Sends reports when any app starts or stops capturing from the microphone or
+// camera, e.g. an "on air" light during calls.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct CaptureInUseConfig {
+    #[serde(default)]
+    pub device: CaptureDevice,
+    #[serde(default)]
+    pub in_use_reports: Vec<Report>,
+    #[serde(default)]
+    pub idle_reports: Vec<Report>,
+}
+
+impl CaptureInUseConfig {
+    pub fn reports(&self, in_use: bool) -> &[Report] {
+        if in_use {
+            &self.in_use_reports
+        } else {
+            &self.idle_reports
+        }
+    }
+}
+
+// Sends reports when the media player matches, e.g. a specific artist
+// playing, again on every track change while it does. Text fields match
+// case-insensitively anywhere, unset ones match anything.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct MediaConfig {
+    #[serde(default)]
+    pub status: Option<PlaybackStatus>,
+    #[serde(default)]
+    pub player: Option<String>,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub on_match_reports: Vec<Report>,
+    #[serde(default)]
+    pub on_no_match_reports: Vec<Report>,
+}
+
+impl MediaConfig {
+    pub fn is_match(&self, media: &MediaState) -> bool {
+        let contains = |pattern: &Option<String>, value: &Option<String>| match pattern {
+            None => true,
+            Some(pattern) => value
+                .as_ref()
+                .is_some_and(|value| value.to_lowercase().contains(&pattern.to_lowercase())),
+        };
+
+        self.status.is_none_or(|status| status == media.status)
+            && contains(&self.player, &media.player)
+            && contains(&self.artist, &media.artist)
+            && contains(&self.title, &media.title)
+    }
+
+    pub fn reports(&self, is_match: bool) -> &[Report] {
+        if is_match {
+            &self.on_match_reports
+        } else {
+            &self.on_no_match_reports
+        }
+    }
+}
+
+// Sends reports when the clipboard changes to matching content, again on
+// every matching copy, e.g. a warning light while a secret is copied. The
+// pattern is a regex searched in the clipboard's text. With
+// `revert_after_secs`, no match reports follow a match after that long.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ClipboardConfig {
+    #[serde(default)]
+    pub content_type: ClipboardContentType,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub revert_after_secs: Option<u64>,
+    #[serde(default)]
+    pub on_match_reports: Vec<Report>,
+    #[serde(default)]
+    pub on_no_match_reports: Vec<Report>,
+}
+
+impl ClipboardConfig {
+    pub fn is_match(&self, content: &ClipboardContent) -> bool {
+        if !content.has(self.content_type) {
+            return false;
+        }
+
+        let Some(pattern) = &self.pattern else {
+            return true;
+        };
+        match regex::Regex::new(pattern) {
+            Ok(regex) => content
+                .text
+                .as_ref()
+                .is_some_and(|text| regex.is_match(text)),
+            Err(e) => {
+                tracing::error!("ClipboardConfig: invalid pattern {}: {}", pattern, e);
+                false
+            }
+        }
+    }
+
+    pub fn reports(&self, is_match: bool) -> &[Report] {
+        if is_match {
+            &self.on_match_reports
+        } else {
+            &self.on_no_match_reports
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct WorkspaceReports {
+    // The workspace's name, or its index when it has none.
+    pub workspace: String,
+    pub reports: Vec<Report>,
+}
+
+// Sends the reports of the active workspace, `other_reports` for any
+// workspace not in the table.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct WorkspaceChangedConfig {
+    pub workspaces: Vec<WorkspaceReports>,
+    #[serde(default)]
+    pub other_reports: Vec<Report>,
+}
+
+impl WorkspaceChangedConfig {
+    pub fn reports(&self, workspace: &str) -> &[Report] {
+        self.workspaces
+            .iter()
+            .find(|entry| entry.workspace == workspace)
+            .map_or(&self.other_reports, |entry| &entry.reports)
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FileContentsReports {
+    // A regex searched in the file, or in the value at `pointer` when set.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    // A JSON pointer into the file, e.g. `/status`. Strings are matched
+    // without their quotes.
+    #[serde(default)]
+    pub pointer: Option<String>,
+    pub reports: Vec<Report>,
+}
+
+impl FileContentsReports {
+    pub fn is_match(&self, contents: &str) -> bool {
+        let value = match &self.pointer {
+            Some(pointer) => {
+                let Ok(json) = serde_json::from_str::<serde_json::Value>(contents) else {
+                    return false;
+                };
+                match json.pointer(pointer) {
+                    Some(serde_json::Value::String(value)) => value.clone(),
+                    Some(value) => value.to_string(),
+                    None => return false,
+                }
+            }
+            None => contents.to_string(),
+        };
+
+        let Some(pattern) = &self.pattern else {
+            return true;
+        };
+        match regex::Regex::new(pattern) {
+            Ok(regex) => regex.is_match(&value),
+            Err(e) => {
+                tracing::error!("FileContentsReports: invalid pattern {}: {}", pattern, e);
+                false
+            }
+        }
+    }
+}
+
+// Sends reports when a file is created, written or deleted, e.g. a status
+// file written by a script. With `contents`, the first entry matching the
+// file's contents is sent whenever that changes, `no_contents_match_reports`
+// when none does. A leading `~` in the path is the home directory.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FileWatchConfig {
+    pub path: String,
+    #[serde(default)]
+    pub on_create_reports: Vec<Report>,
+    #[serde(default)]
+    pub on_modify_reports: Vec<Report>,
+    #[serde(default)]
+    pub on_delete_reports: Vec<Report>,
+    #[serde(default)]
+    pub contents: Vec<FileContentsReports>,
+    #[serde(default)]
+    pub no_contents_match_reports: Vec<Report>,
+}
+
+impl FileWatchConfig {
+    pub fn path(&self) -> PathBuf {
+        filewatch::expand_path(self.path.trim())
+    }
+
+    pub fn change_reports(&self, change: FileChange) -> &[Report] {
+        match change {
+            FileChange::Create => &self.on_create_reports,
+            FileChange::Modify => &self.on_modify_reports,
+            FileChange::Delete => &self.on_delete_reports,
+        }
+    }
+
+    // The index of the first entry matching the file's contents.
+    pub fn contents_match(&self, contents: &str) -> Option<usize> {
+        self.contents
+            .iter()
+            .position(|entry| entry.is_match(contents))
+    }
+
+    pub fn contents_reports(&self, matched: Option<usize>) -> &[Report] {
+        matched
+            .and_then(|index| self.contents.get(index))
+            .map_or(&self.no_contents_match_reports, |entry| &entry.reports)
+    }
+}
+
+// Sends reports when a POST request arrives at `/webhook/<name>` on the
+// webhook listener, e.g. from a build script. Fields of the request's JSON
+// body are available to templates as `{body:...}` placeholders.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    pub name: String,
+    #[serde(default)]
+    pub reports: Vec<Report>,
+}
+
+fn default_webhook_port() -> u16 {
+    7878
+}
+
+// The HTTP listener for webhook rules, bound to localhost. Requests must carry
+// `Authorization: Bearer <token>`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookListenerConfig {
+    #[serde(default = "default_webhook_port")]
+    pub port: u16,
+    pub token: String,
+}
+
+impl WebhookListenerConfig {
+    // Compares in constant time, so the token can't be guessed byte by byte.
+    pub fn is_authorized(&self, token: Option<&str>) -> bool {
+        let (Some(token), expected) = (token, self.token.as_bytes()) else {
+            return false;
+        };
+        !expected.is_empty()
+            && token.len() == expected.len()
+            && token
+                .bytes()
+                .zip(expected)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+fn default_history_capacity() -> usize {
+    1000
+}
+
+// How much of the event history is kept, see `history`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistoryConfig {
+    #[serde(default = "default_history_capacity")]
+    pub capacity: usize,
+    // Also appends events to this JSON lines file, and restores them from it
+    // on start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_history_capacity(),
+            file: None,
+        }
+    }
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
 }
 
-impl Default for Event {
+fn default_max_log_files() -> usize {
+    7
+}
+
+// Read on start, see `logging`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LoggingConfig {
+    // A level such as `debug`, or per module directives such as
+    // `info,locked_in::hid=trace`. RUST_LOG takes precedence when set.
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    // Log files rotate daily, older ones are deleted.
+    #[serde(default = "default_max_log_files")]
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
     fn default() -> Self {
-        Self::FocusedWindowChanged(FocusedWindowChangedConfig::default())
+        Self {
+            level: default_log_level(),
+            max_files: default_max_log_files(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    // Just the [logging] table, read before the rest of the config so that
+    // logging is set up first and problems loading the config get logged.
+    pub fn read() -> Result<Self> {
+        let mut table = Figment::new()
+            .merge(Toml::file(CONFIG_PATH))
+            .extract::<toml::Table>()
+            .context("Failed to load config.toml")?;
+        match table.remove("logging") {
+            Some(logging) => logging
+                .try_into()
+                .context("Failed to parse [logging] in config.toml"),
+            None => Ok(Self::default()),
+        }
     }
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
-pub struct FocusedWindowChangedConfig {
-    pub inclusions: Vec<WindowMetadata>,
-    pub exclusions: Vec<WindowMetadata>,
-    pub on_match_reports: Vec<Vec<u8>>,
-    pub on_no_match_reports: Vec<Vec<u8>>,
+pub struct LayoutReports {
+    // A locale name such as `en-US` on Windows, the XKB group name such as
+    // `English (US)` on X11. Compared case-insensitively.
+    pub layout: String,
+    pub reports: Vec<Report>,
+}
+
+// Sends the reports of the active input layout, `other_reports` for any layout
+// not in the table.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct KeyboardLayoutChangedConfig {
+    pub layouts: Vec<LayoutReports>,
+    #[serde(default)]
+    pub other_reports: Vec<Report>,
+}
+
+impl KeyboardLayoutChangedConfig {
+    pub fn reports(&self, layout: &str) -> &[Report] {
+        self.layouts
+            .iter()
+            .find(|entry| entry.layout.eq_ignore_ascii_case(layout.trim()))
+            .map_or(&self.other_reports, |entry| &entry.reports)
+    }
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -52,64 +962,304 @@ pub struct Device {
     pub usage: u16,
     pub report_length: u16,
     pub report_id: u8,
+    // Optional pins used to tell identical devices apart.
+    pub serial_number: Option<String>,
+    pub interface_number: Option<i32>,
+    pub path: Option<String>,
+    #[serde(default)]
+    pub target: DeviceTarget,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct GroupMember {
+    pub device: String,
+    // Overrides for the member device, the rule's reports stay the same.
+    pub report_id: Option<u8>,
+    pub report_length: Option<u16>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct DeviceGroup {
+    pub name: String,
+    #[serde(default)]
+    pub members: Vec<GroupMember>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Rule {
     pub name: String,
     pub event: Event,
-    pub devices: Vec<Device>,
+    // Names of devices in the config's device registry.
+    #[serde(default)]
+    pub devices: Vec<String>,
+    // Names of device groups, reports fan out to every connected member.
+    #[serde(default)]
+    pub groups: Vec<String>,
+    // Disabled rules send nothing, e.g. while switched off over the control
+    // socket.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+}
+
+// A rule as listed over the control socket and on the command line.
+#[derive(Debug, Serialize)]
+pub struct RuleSummary {
+    pub name: String,
+    pub event: String,
+    pub enabled: bool,
+    pub devices: Vec<String>,
+    pub groups: Vec<String>,
 }
 
 impl Rule {
-    pub fn trigger(&self, window: &WindowMetadata) {
-        match &self.event {
-            Event::FocusedWindowChanged(event_cfg) => {
-                let exclusion_found = event_cfg
-                    .exclusions
-                    .iter()
-                    .any(|exclusion| window.match_any(exclusion));
+    pub fn summary(&self) -> RuleSummary {
+        RuleSummary {
+            name: self.name.clone(),
+            event: self.event.to_string(),
+            enabled: !self.disabled,
+            devices: self.devices.clone(),
+            groups: self.groups.clone(),
+        }
+    }
 
-                let inclusion_found = event_cfg
-                    .inclusions
-                    .iter()
-                    .any(|inclusion| window.match_any(inclusion));
+    // The reports a focused window rule sends for the window, None for other
+    // rules.
+    pub fn window_reports(&self, window: &WindowMetadata) -> Option<&[Report]> {
+        let Event::FocusedWindowChanged(event_cfg) = &self.event else {
+            return None;
+        };
+        Some(event_cfg.reports(event_cfg.is_match(window)))
+    }
 
-                let reports = if exclusion_found || !inclusion_found {
-                    &event_cfg.on_no_match_reports
-                } else {
-                    &event_cfg.on_match_reports
-                };
+    #[tracing::instrument(name = "evaluate_rule", skip_all, fields(rule = %self.name))]
+    pub fn trigger(&self, config: &Config, window: &WindowMetadata) {
+        let Event::FocusedWindowChanged(event_cfg) = &self.event else {
+            return;
+        };
+        if self.disabled {
+            return;
+        }
 
-                for device in &self.devices {
-                    for report in reports {
-                        if let Err(e) = device.send_report(report) {
-                            eprintln!("Failed to send report to device {}: {}", device.name, e);
-                        }
-                    }
-                }
-            }
+        let matched = event_cfg.is_match(window);
+        tracing::debug!(matched, title = ?window.title, class = ?window.class, "evaluated");
+        activity::publish(Activity::RuleEvaluated {
+            rule: self.name.clone(),
+            window: window.clone(),
+            matched,
+        });
+        self.send(config, event_cfg.reports(matched));
+    }
+
+    pub fn send(&self, config: &Config, reports: &[Report]) {
+        self.send_with_body(config, reports, &serde_json::Value::Null);
+    }
+
+    // Like send, with the webhook request's JSON body for `{body:...}`
+    // placeholders.
+    pub fn send_with_body(&self, config: &Config, reports: &[Report], body: &serde_json::Value) {
+        if self.disabled {
+            return;
+        }
+        self.send_reports(config, config.resolve_devices(self), reports, body);
+    }
+
+    // Sends the interval rule's reports to its connected devices. Returns
+    // false if none are connected, so the interval stays paused.
+    pub fn tick(&self, config: &Config) -> bool {
+        let Event::Interval(event_cfg) = &self.event else {
+            return false;
+        };
+        if self.disabled {
+            return false;
+        }
+
+        let devices: Vec<Device> = config
+            .resolve_devices(self)
+            .into_iter()
+            .filter(Device::is_connected)
+            .collect();
+
+        if devices.is_empty() {
+            return false;
+        }
+
+        self.send_reports(
+            config,
+            devices,
+            &event_cfg.reports,
+            &serde_json::Value::Null,
+        );
+        true
+    }
+
+    #[tracing::instrument(
+        name = "send_rule",
+        skip_all,
+        fields(rule = %self.name, event = %self.event)
+    )]
+    fn send_reports(
+        &self,
+        config: &Config,
+        devices: Vec<Device>,
+        reports: &[Report],
+        body: &serde_json::Value,
+    ) {
+        let device_names: Vec<String> = devices.iter().map(|device| device.name.clone()).collect();
+        tracing::debug!(devices = ?device_names, reports = reports.len(), "sending");
+        activity::publish(Activity::RuleSent {
+            rule: self.name.clone(),
+            event: self.event.to_string(),
+            devices: device_names,
+            reports: reports.len(),
+        });
+
+        for device in devices {
+            let actions = reports
+                .iter()
+                .flat_map(|report| report.actions(config, &device))
+                .collect();
+            sequencer::run(&self.name, device, actions, body.clone());
         }
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Config {
+    #[serde(default)]
+    pub devices: Vec<Device>,
+    #[serde(default)]
+    pub groups: Vec<DeviceGroup>,
+    #[serde(default)]
+    pub reports: Vec<ReportDefinition>,
     #[serde(default)]
     pub rules: Vec<Rule>,
+    // Only listens for webhooks when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookListenerConfig>,
+    // The in-memory history is kept with the defaults when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logging: Option<LoggingConfig>,
+}
+
+// Rules used to embed full `[[rules.devices]]` tables. Moves any such tables
+// into the top-level device registry and replaces them with the device's name.
+// Returns whether anything was migrated.
+fn migrate_inline_devices(table: &mut toml::Table) -> bool {
+    let mut registry: Vec<toml::Value> = match table.remove("devices") {
+        Some(toml::Value::Array(devices)) => devices,
+        _ => Vec::new(),
+    };
+    // Inline tables as they were written, with the name they were registered
+    // under, which differs from theirs when it clashed.
+    let mut assigned: Vec<(toml::Table, String)> = Vec::new();
+    let mut migrated = false;
+
+    if let Some(toml::Value::Array(rules)) = table.get_mut("rules") {
+        for rule in rules.iter_mut().filter_map(toml::Value::as_table_mut) {
+            let Some(toml::Value::Array(devices)) = rule.get_mut("devices") else {
+                continue;
+            };
+
+            for entry in devices.iter_mut() {
+                let toml::Value::Table(inline) = entry else {
+                    continue;
+                };
+
+                let name_of = |device: &toml::Value| {
+                    device
+                        .get("name")
+                        .and_then(toml::Value::as_str)
+                        .map(str::to_string)
+                };
+
+                // The same device is often repeated across rules, only
+                // register it once.
+                let name = if let Some((_, name)) =
+                    assigned.iter().find(|(original, _)| original == inline)
+                {
+                    name.clone()
+                } else if let Some(name) = registry
+                    .iter()
+                    .find(|device| device.as_table() == Some(&*inline))
+                    .and_then(name_of)
+                {
+                    name
+                } else {
+                    let original = inline.clone();
+                    let base_name = inline
+                        .get("name")
+                        .and_then(toml::Value::as_str)
+                        .unwrap_or("Untitled")
+                        .to_string();
+                    let mut name = base_name.clone();
+                    let mut suffix = 2;
+                    while registry
+                        .iter()
+                        .any(|device| name_of(device).as_deref() == Some(name.as_str()))
+                    {
+                        name = format!("{base_name} ({suffix})");
+                        suffix += 1;
+                    }
+                    inline.insert("name".into(), name.clone().into());
+                    registry.push(toml::Value::Table(inline.clone()));
+                    assigned.push((original, name.clone()));
+                    name
+                };
+
+                *entry = toml::Value::String(name);
+                migrated = true;
+            }
+        }
+    }
+
+    table.insert("devices".into(), toml::Value::Array(registry));
+    migrated
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
-        let path = env::current_dir()
+    pub fn path() -> Result<PathBuf> {
+        Ok(env::current_dir()
             .context("Failed to get current directory")?
-            .join(CONFIG_PATH);
+            .join(CONFIG_PATH))
+    }
+
+    // Parses config.toml without writing to it, also returns whether it is in
+    // an older format which load() would migrate.
+    pub fn read() -> Result<(Self, bool)> {
+        let mut table = Figment::new()
+            .merge(Toml::file(CONFIG_PATH))
+            .extract::<toml::Table>()
+            .context("Failed to load config.toml")?;
+
+        let needs_migration = migrate_inline_devices(&mut table);
+
+        let config = toml::Value::Table(table)
+            .try_into::<Config>()
+            .context("Failed to parse config.toml")?;
+        Ok((config, needs_migration))
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
 
         if path.is_file() {
-            return Figment::new()
-                .merge(Toml::file(CONFIG_PATH))
-                .extract::<Config>()
-                .context("Failed to load config.toml");
+            let (config, migrated) = Self::read()?;
+
+            if migrated {
+                let mut backup_path = path.clone().into_os_string();
+                backup_path.push(".bak");
+                fs::copy(&path, &backup_path).with_context(|| {
+                    format!(
+                        "Failed to back up config file to: {}",
+                        backup_path.display()
+                    )
+                })?;
+                config.save().context("Failed to save migrated config")?;
+            }
+
+            return Ok(config);
         }
 
         if let Some(parent) = path.parent()
@@ -144,9 +1294,7 @@ impl Config {
             dest.with_file_name(tmp_name)
         }
 
-        let path = env::current_dir()
-            .context("Failed to get current directory")?
-            .join(CONFIG_PATH);
+        let path = Self::path()?;
 
         let toml_string =
             toml::to_string_pretty(self).context("Failed to serialize config to TOML")?;
@@ -197,4 +1345,579 @@ impl Config {
     pub fn get_mut_rule(&mut self, name: &str) -> Option<&mut Rule> {
         self.rules.iter_mut().find(|r| r.name == name)
     }
+
+    pub fn get_device(&self, name: &str) -> Option<&Device> {
+        self.devices.iter().find(|d| d.name == name)
+    }
+
+    pub fn get_group(&self, name: &str) -> Option<&DeviceGroup> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    // Resolves the rule's devices and groups to the devices its reports should
+    // be sent to, with group member overrides applied. Disconnected group
+    // members are skipped, a device is only included once.
+    pub fn resolve_devices(&self, rule: &Rule) -> Vec<Device> {
+        let mut devices = self.resolve_all_devices(rule);
+        devices.retain(|device| rule.devices.contains(&device.name) || device.is_connected());
+        devices
+    }
+
+    // Like resolve_devices, but keeps disconnected group members, e.g. to
+    // preview what they would be sent.
+    pub fn resolve_all_devices(&self, rule: &Rule) -> Vec<Device> {
+        let mut devices: Vec<Device> = Vec::new();
+
+        for name in &rule.devices {
+            match self.get_device(name) {
+                Some(device) => {
+                    if !devices.iter().any(|d| d.name == device.name) {
+                        devices.push(device.clone());
+                    }
+                }
+                None => tracing::warn!("Rule {} references unknown device {}", rule.name, name),
+            }
+        }
+
+        for group_name in &rule.groups {
+            let Some(group) = self.get_group(group_name) else {
+                tracing::warn!("Rule {} references unknown group {}", rule.name, group_name);
+                continue;
+            };
+
+            for member in &group.members {
+                let Some(device) = self.get_device(&member.device) else {
+                    tracing::warn!(
+                        "Group {} references unknown device {}",
+                        group.name,
+                        member.device
+                    );
+                    continue;
+                };
+
+                if devices.iter().any(|d| d.name == device.name) {
+                    continue;
+                }
+
+                let mut device = device.clone();
+                if let Some(report_id) = member.report_id {
+                    device.report_id = report_id;
+                }
+                if let Some(report_length) = member.report_length {
+                    device.report_length = report_length;
+                }
+                devices.push(device);
+            }
+        }
+
+        devices
+    }
+
+    // Returns `name`, suffixed if needed so it doesn't clash with any device
+    // other than the one at `except`.
+    pub fn unique_device_name(&self, name: &str, except: Option<usize>) -> String {
+        let taken = |candidate: &str| {
+            self.devices
+                .iter()
+                .enumerate()
+                .any(|(i, d)| Some(i) != except && d.name == candidate)
+        };
+
+        let mut unique = name.to_string();
+        let mut suffix = 2;
+        while taken(&unique) {
+            unique = format!("{name} ({suffix})");
+            suffix += 1;
+        }
+        unique
+    }
+
+    pub fn rename_device(&mut self, old_name: &str, new_name: &str) {
+        if let Some(device) = self.devices.iter_mut().find(|d| d.name == old_name) {
+            device.name = new_name.to_string();
+        }
+        for rule in self.rules.iter_mut() {
+            for name in rule.devices.iter_mut().filter(|n| *n == old_name) {
+                *name = new_name.to_string();
+            }
+        }
+        for group in self.groups.iter_mut() {
+            for member in group.members.iter_mut().filter(|m| m.device == old_name) {
+                member.device = new_name.to_string();
+            }
+        }
+        for reports in self.report_lists_mut() {
+            for report in reports.iter_mut() {
+                report.rename_device_reference(old_name, new_name);
+            }
+        }
+    }
+
+    pub fn delete_device(&mut self, name: &str) {
+        self.devices.retain(|d| d.name != name);
+        for rule in self.rules.iter_mut() {
+            rule.devices.retain(|n| n != name);
+        }
+        for group in self.groups.iter_mut() {
+            group.members.retain(|m| m.device != name);
+        }
+        for reports in self.report_lists_mut() {
+            reports.retain(|report| !report.references_device(name));
+        }
+    }
+
+    pub fn unique_group_name(&self, name: &str, except: Option<usize>) -> String {
+        let taken = |candidate: &str| {
+            self.groups
+                .iter()
+                .enumerate()
+                .any(|(i, g)| Some(i) != except && g.name == candidate)
+        };
+
+        let mut unique = name.to_string();
+        let mut suffix = 2;
+        while taken(&unique) {
+            unique = format!("{name} ({suffix})");
+            suffix += 1;
+        }
+        unique
+    }
+
+    pub fn rename_group(&mut self, old_name: &str, new_name: &str) {
+        if let Some(group) = self.groups.iter_mut().find(|g| g.name == old_name) {
+            group.name = new_name.to_string();
+        }
+        for rule in self.rules.iter_mut() {
+            for name in rule.groups.iter_mut().filter(|n| *n == old_name) {
+                *name = new_name.to_string();
+            }
+        }
+    }
+
+    pub fn delete_group(&mut self, name: &str) {
+        self.groups.retain(|g| g.name != name);
+        for rule in self.rules.iter_mut() {
+            rule.groups.retain(|n| n != name);
+        }
+    }
+
+    pub fn get_report(&self, name: &str) -> Option<&ReportDefinition> {
+        self.reports.iter().find(|r| r.name == name)
+    }
+
+    pub fn unique_report_name(&self, name: &str, except: Option<usize>) -> String {
+        let taken = |candidate: &str| {
+            self.reports
+                .iter()
+                .enumerate()
+                .any(|(i, r)| Some(i) != except && r.name == candidate)
+        };
+
+        let mut unique = name.to_string();
+        let mut suffix = 2;
+        while taken(&unique) {
+            unique = format!("{name} ({suffix})");
+            suffix += 1;
+        }
+        unique
+    }
+
+    // Every report list that may reference the library, in rules and in the
+    // library itself.
+    fn report_lists_mut(&mut self) -> impl Iterator<Item = &mut Vec<Report>> {
+        self.rules
+            .iter_mut()
+            .flat_map(|rule| rule.event.report_lists_mut())
+            .chain(self.reports.iter_mut().map(|r| &mut r.reports))
+    }
+
+    pub fn rename_report(&mut self, old_name: &str, new_name: &str) {
+        if let Some(definition) = self.reports.iter_mut().find(|r| r.name == old_name) {
+            definition.name = new_name.to_string();
+        }
+        for reports in self.report_lists_mut() {
+            for report in reports.iter_mut() {
+                report.rename_report_reference(old_name, new_name);
+            }
+        }
+    }
+
+    pub fn delete_report(&mut self, name: &str) {
+        self.reports.retain(|r| r.name != name);
+        for reports in self.report_lists_mut() {
+            reports.retain(|report| !report.references_report(name));
+        }
+    }
+
+    // Problems that only show up once rules run, e.g. a reference to a device
+    // missing from the registry. Empty when the config is fine.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for (kind, names) in [
+            (
+                "rule",
+                self.rules.iter().map(|r| &r.name).collect::<Vec<_>>(),
+            ),
+            ("device", self.devices.iter().map(|d| &d.name).collect()),
+            ("group", self.groups.iter().map(|g| &g.name).collect()),
+            ("report", self.reports.iter().map(|r| &r.name).collect()),
+        ] {
+            for (i, name) in names.iter().enumerate() {
+                if names[..i].contains(name) {
+                    problems.push(format!("Duplicate {kind} name {name}"));
+                }
+            }
+        }
+
+        for group in &self.groups {
+            for member in &group.members {
+                if self.get_device(&member.device).is_none() {
+                    problems.push(format!(
+                        "Group {} references unknown device {}",
+                        group.name, member.device
+                    ));
+                }
+            }
+        }
+
+        for definition in &self.reports {
+            let owner = format!("Report {}", definition.name);
+            for report in &definition.reports {
+                self.validate_report(report, &owner, &mut problems);
+            }
+        }
+
+        for rule in &self.rules {
+            let owner = format!("Rule {}", rule.name);
+            if rule.devices.is_empty() && rule.groups.is_empty() {
+                problems.push(format!("{owner} has no devices or groups"));
+            }
+            for name in &rule.devices {
+                if self.get_device(name).is_none() {
+                    problems.push(format!("{owner} references unknown device {name}"));
+                }
+            }
+            for name in &rule.groups {
+                if self.get_group(name).is_none() {
+                    problems.push(format!("{owner} references unknown group {name}"));
+                }
+            }
+
+            // A device is only sent to once, as it is first listed: directly
+            // on the rule, then in group order.
+            let mut targets: Vec<(&str, u8, u16, String)> = rule
+                .devices
+                .iter()
+                .filter_map(|name| self.get_device(name))
+                .map(|device| {
+                    (
+                        device.name.as_str(),
+                        device.report_id,
+                        device.report_length,
+                        "directly".to_string(),
+                    )
+                })
+                .collect();
+            for group in rule.groups.iter().filter_map(|name| self.get_group(name)) {
+                for member in &group.members {
+                    let Some(device) = self.get_device(&member.device) else {
+                        continue;
+                    };
+                    let report_id = member.report_id.unwrap_or(device.report_id);
+                    let report_length = member.report_length.unwrap_or(device.report_length);
+                    match targets.iter().find(|(name, ..)| *name == device.name) {
+                        Some((_, first_id, first_length, via))
+                            if (*first_id, *first_length) != (report_id, report_length) =>
+                        {
+                            problems.push(format!(
+                                "{owner} targets device {} {via} and through group {} with different report settings, the group's are ignored",
+                                device.name, group.name
+                            ));
+                        }
+                        Some(_) => {}
+                        None => targets.push((
+                            device.name.as_str(),
+                            report_id,
+                            report_length,
+                            format!("through group {}", group.name),
+                        )),
+                    }
+                }
+            }
+
+            for reports in rule.event.report_lists() {
+                for report in reports.iter() {
+                    self.validate_report(report, &owner, &mut problems);
+                }
+            }
+
+            let mut patterns = Vec::new();
+            match &rule.event {
+                Event::Clipboard(event_cfg) => patterns.push(&event_cfg.pattern),
+                Event::FileWatch(event_cfg) => {
+                    if event_cfg.path.trim().is_empty() {
+                        problems.push(format!("{owner} has no path to watch"));
+                    }
+                    patterns.extend(event_cfg.contents.iter().map(|entry| &entry.pattern));
+                }
+                Event::Webhook(event_cfg) => {
+                    if event_cfg.name.is_empty() {
+                        problems.push(format!("{owner} has no webhook name"));
+                    }
+                    if self.webhook.is_none() {
+                        problems.push(format!(
+                            "{owner} needs the webhook listener, add a [webhook] table"
+                        ));
+                    }
+                }
+                _ => {}
+            }
+            for pattern in patterns.into_iter().flatten() {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    problems.push(format!("{owner} has an invalid pattern {pattern}: {e}"));
+                }
+            }
+        }
+
+        if self
+            .webhook
+            .as_ref()
+            .is_some_and(|listener| listener.token.is_empty())
+        {
+            problems.push("The webhook listener needs a token".to_string());
+        }
+
+        if let Some(logging) = &self.logging
+            && let Err(e) = logging::parse_filter(&logging.level)
+        {
+            problems.push(format!("Invalid log level {}: {e}", logging.level));
+        }
+
+        problems
+    }
+
+    fn validate_report(&self, report: &Report, owner: &str, problems: &mut Vec<String>) {
+        match report {
+            Report::Named(name) if self.get_report(name).is_none() => {
+                problems.push(format!("{owner} references unknown report {name}"));
+            }
+            Report::Step(step) => {
+                if let Some(device) = &step.device
+                    && self.get_device(device).is_none()
+                {
+                    problems.push(format!("{owner} references unknown device {device}"));
+                }
+                if let Some(send) = &step.send {
+                    self.validate_report(send, owner, problems);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device_names(table: &toml::Table) -> Vec<&str> {
+        table["devices"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|device| device["name"].as_str().unwrap())
+            .collect()
+    }
+
+    fn rule_devices(table: &toml::Table, rule: usize) -> Vec<&str> {
+        table["rules"][rule]["devices"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|device| device.as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn migrates_inline_devices() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            [[rules]]
+            name = "a"
+            devices = [{ name = "Pad", vid = 1, pid = 2, usage_page = 3, usage = 4, report_length = 32, report_id = 0 }]
+            "#,
+        )
+        .unwrap();
+
+        assert!(migrate_inline_devices(&mut table));
+        assert_eq!(device_names(&table), ["Pad"]);
+        assert_eq!(rule_devices(&table, 0), ["Pad"]);
+        assert_eq!(table["devices"][0]["vid"].as_integer(), Some(1));
+    }
+
+    #[test]
+    fn renames_clashing_devices_once() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            [[devices]]
+            name = "Pad"
+            vid = 1
+            pid = 1
+            usage_page = 1
+            usage = 1
+            report_length = 32
+            report_id = 0
+
+            [[rules]]
+            name = "a"
+            devices = [{ name = "Pad", vid = 2, pid = 2, usage_page = 1, usage = 1, report_length = 32, report_id = 0 }]
+
+            [[rules]]
+            name = "b"
+            devices = [{ name = "Pad", vid = 2, pid = 2, usage_page = 1, usage = 1, report_length = 32, report_id = 0 }]
+            "#,
+        )
+        .unwrap();
+
+        assert!(migrate_inline_devices(&mut table));
+        assert_eq!(device_names(&table), ["Pad", "Pad (2)"]);
+        assert_eq!(rule_devices(&table, 0), ["Pad (2)"]);
+        assert_eq!(rule_devices(&table, 1), ["Pad (2)"]);
+    }
+
+    #[test]
+    fn reuses_identical_registry_devices() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            [[devices]]
+            name = "Pad"
+            vid = 1
+            pid = 1
+            usage_page = 1
+            usage = 1
+            report_length = 32
+            report_id = 0
+
+            [[rules]]
+            name = "a"
+            devices = [{ name = "Pad", vid = 1, pid = 1, usage_page = 1, usage = 1, report_length = 32, report_id = 0 }]
+            "#,
+        )
+        .unwrap();
+
+        assert!(migrate_inline_devices(&mut table));
+        assert_eq!(device_names(&table), ["Pad"]);
+        assert_eq!(rule_devices(&table, 0), ["Pad"]);
+    }
+
+    #[test]
+    fn leaves_migrated_configs_alone() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            [[rules]]
+            name = "a"
+            devices = ["Pad"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(!migrate_inline_devices(&mut table));
+        assert_eq!(rule_devices(&table, 0), ["Pad"]);
+    }
+
+    #[test]
+    fn deleting_a_device_drops_its_steps() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[devices]]
+            name = "Pad"
+            vid = 1
+            pid = 1
+            usage_page = 1
+            usage = 1
+            report_length = 32
+            report_id = 0
+
+            [[rules]]
+            name = "a"
+            devices = ["Pad"]
+            event = { type = "interval", interval_secs = 1, reports = [
+                [1],
+                { send = [2], device = "Pad" },
+                { send = { send = [3], device = "Pad" }, repeat = 2 },
+                { send = [4], device = "Other" },
+            ] }
+
+            [[reports]]
+            name = "r"
+            reports = [{ wait_ms = 10, device = "Pad" }, [5]]
+            "#,
+        )
+        .unwrap();
+
+        config.delete_device("Pad");
+
+        assert!(config.devices.is_empty());
+        assert!(config.rules[0].devices.is_empty());
+        let Event::Interval(event_cfg) = &config.rules[0].event else {
+            panic!("not an interval rule");
+        };
+        assert_eq!(event_cfg.reports.len(), 2);
+        assert_eq!(event_cfg.reports[0], Report::Bytes(vec![1]));
+        assert!(event_cfg.reports[1].references_device("Other"));
+        assert_eq!(config.reports[0].reports, [Report::Bytes(vec![5])]);
+    }
+
+    fn on_battery(percent: u8) -> PowerStatus {
+        PowerStatus {
+            on_ac: false,
+            battery_percent: Some(percent),
+        }
+    }
+
+    #[test]
+    fn power_state_from_status() {
+        let power = PowerConfig::default();
+        let ac = PowerStatus {
+            on_ac: true,
+            battery_percent: Some(5),
+        };
+        let no_battery = PowerStatus {
+            on_ac: false,
+            battery_percent: None,
+        };
+
+        assert_eq!(power.state(&ac, None), PowerState::Ac);
+        assert_eq!(power.state(&no_battery, None), PowerState::Battery);
+        assert_eq!(power.state(&on_battery(21), None), PowerState::Battery);
+        assert_eq!(power.state(&on_battery(20), None), PowerState::LowBattery);
+    }
+
+    // Low battery is only left once the charge is above 20 + 5.
+    #[test]
+    fn low_battery_hysteresis() {
+        let power = PowerConfig::default();
+        let mut state = None;
+        let states: Vec<PowerState> = [22, 20, 22, 25, 26, 24]
+            .into_iter()
+            .map(|percent| {
+                let next = power.state(&on_battery(percent), state);
+                state = Some(next);
+                next
+            })
+            .collect();
+
+        assert_eq!(
+            states,
+            [
+                PowerState::Battery,
+                PowerState::LowBattery,
+                PowerState::LowBattery,
+                PowerState::LowBattery,
+                PowerState::Battery,
+                PowerState::Battery,
+            ]
+        );
+    }
 }
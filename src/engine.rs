@@ -0,0 +1,499 @@
+use anyhow::{Context, Result};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+
+use crate::{
+    activity, capture, clipboard,
+    config::{self, Config},
+    filewatch, hid, history, layout, media, metrics, net, power, rpc, webhook, win, workspace,
+};
+
+// Rule evaluation, independent of the UI so it can run headless. The UI embeds
+// it, or edits the config of a headless instance over the control socket.
+
+// The config rules are evaluated against. Changes made by the engine itself,
+// e.g. over the control socket, notify subscribers such as the UI.
+pub static CONFIG_TX: LazyLock<watch::Sender<Config>> = LazyLock::new(|| {
+    let (tx, _rx) = watch::channel(Config::load().expect("Failed to load config"));
+    tx
+});
+
+// Must not be held across an await, writers would block.
+pub fn config() -> watch::Ref<'static, Config> {
+    CONFIG_TX.borrow()
+}
+
+// Replaces the config with the UI's copy, without notifying the UI back.
+pub fn set_config(config: Config) {
+    CONFIG_TX.send_if_modified(|current| {
+        *current = config;
+        false
+    });
+}
+
+pub fn update_config(update: impl FnOnce(&mut Config)) {
+    CONFIG_TX.send_modify(update);
+}
+
+// Starts the platform watchers feeding the engine, the returned foreground
+// hook must be kept alive.
+pub fn start_watchers() -> Result<impl Sized> {
+    let foreground_hook = win::start_foreground_hook();
+    layout::start_layout_watcher();
+    workspace::start_workspace_watcher();
+    clipboard::start_clipboard_watcher();
+    foreground_hook
+}
+
+// Evaluates rules until every event source has stopped, which in practice is
+// never.
+pub async fn run() {
+    tokio::join!(
+        // First, so it subscribes before anything is published.
+        history::run(),
+        watch_devices(),
+        watch_focus(),
+        tick_intervals(),
+        watch_power(),
+        watch_network(),
+        watch_capture(),
+        watch_workspaces(),
+        watch_layouts(),
+        watch_players(),
+        watch_media(),
+        watch_clipboard(),
+        watch_files(),
+        serve_rpc(),
+        serve_webhooks(),
+    );
+}
+
+// Runs the engine without any UI, e.g. as a daemon started on login.
+pub fn run_headless() -> Result<()> {
+    let _foreground_hook = start_watchers().context("failed to watch the focused window")?;
+
+    let engine = std::thread::Builder::new()
+        .name("engine".into())
+        .spawn(|| -> Result<()> {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("failed to build the async runtime")?;
+            runtime.block_on(run());
+            Ok(())
+        })
+        .context("failed to spawn engine thread")?;
+
+    #[cfg(windows)]
+    win::pump_messages();
+
+    engine
+        .join()
+        .map_err(|_| anyhow::anyhow!("engine thread panicked"))?
+}
+
+// Keeps the HID device cache current, so group fan-out and paused interval
+// rules follow devices being plugged in and out.
+async fn watch_devices() {
+    let mut ticker = tokio::time::interval(Duration::from_secs(2));
+    loop {
+        ticker.tick().await;
+        if tokio::task::spawn_blocking(hid::refresh_devices)
+            .await
+            .is_err()
+        {
+            activity::error("hid::refresh_devices", "panicked");
+            break;
+        }
+    }
+}
+
+async fn watch_focus() {
+    let mut rx = win::FOCUSED_WINDOW_TX.subscribe();
+    let mut was_fullscreen = None;
+    loop {
+        if rx.changed().await.is_err() {
+            break;
+        }
+        let focused_window = rx.borrow().clone();
+        activity::publish(activity::Activity::FocusedWindowChanged {
+            window: focused_window.clone(),
+        });
+
+        let is_fullscreen = focused_window.is_fullscreen.unwrap_or(false);
+        let fullscreen_changed = was_fullscreen != Some(is_fullscreen);
+        was_fullscreen = Some(is_fullscreen);
+
+        let config = config();
+        for rule in config.rules.iter() {
+            match &rule.event {
+                config::Event::FocusedWindowChanged(_) => {
+                    rule.trigger(&config, &focused_window);
+                }
+                config::Event::FullscreenChanged(event_cfg) if fullscreen_changed => {
+                    rule.send(&config, event_cfg.reports(is_fullscreen));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+async fn tick_intervals() {
+    let mut last_sent: HashMap<String, Instant> = HashMap::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        ticker.tick().await;
+
+        let config = config();
+        let interval_rules: Vec<(&config::Rule, u64)> = config
+            .rules
+            .iter()
+            .filter_map(|rule| match &rule.event {
+                config::Event::Interval(event_cfg) => Some((rule, event_cfg.interval_secs)),
+                _ => None,
+            })
+            .collect();
+
+        if interval_rules.is_empty() {
+            continue;
+        }
+
+        // Paused rules resume, and running ones pause, once `watch_devices`
+        // sees their device plugged in or out.
+        metrics::refresh();
+
+        for (rule, interval_secs) in interval_rules {
+            let due = last_sent
+                .get(&rule.name)
+                .is_none_or(|sent| sent.elapsed() >= Duration::from_secs(interval_secs.max(1)));
+            if due && rule.tick(&config) {
+                last_sent.insert(rule.name.clone(), Instant::now());
+            }
+        }
+    }
+}
+
+async fn watch_power() {
+    let mut states: HashMap<String, power::PowerState> = HashMap::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(5));
+    loop {
+        ticker.tick().await;
+
+        let config = config();
+        let mut status = None;
+        for rule in config.rules.iter() {
+            let config::Event::Power(event_cfg) = &rule.event else {
+                continue;
+            };
+
+            let status = status.get_or_insert_with(power::get_power_status);
+            let previous = states.get(&rule.name).copied();
+            let state = event_cfg.state(status, previous);
+            if previous != Some(state) {
+                rule.send(&config, event_cfg.reports(state));
+                states.insert(rule.name.clone(), state);
+            }
+        }
+    }
+}
+
+async fn watch_network() {
+    let mut matches: HashMap<String, bool> = HashMap::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(2));
+    loop {
+        ticker.tick().await;
+
+        let config = config();
+        let mut interfaces = None;
+        for rule in config.rules.iter() {
+            let config::Event::Network(event_cfg) = &rule.event else {
+                continue;
+            };
+
+            let interfaces = interfaces.get_or_insert_with(net::get_interfaces);
+            let is_match = event_cfg.is_match(interfaces);
+            if matches.insert(rule.name.clone(), is_match) != Some(is_match) {
+                rule.send(&config, event_cfg.reports(is_match));
+            }
+        }
+    }
+}
+
+async fn watch_capture() {
+    let mut provider = capture::system_provider();
+    let mut changes = capture::CaptureChanges::default();
+    let mut ticker = tokio::time::interval(Duration::from_secs(2));
+    loop {
+        ticker.tick().await;
+
+        let has_rules = config()
+            .rules
+            .iter()
+            .any(|rule| matches!(rule.event, config::Event::CaptureInUse(_)));
+        if !has_rules {
+            continue;
+        }
+
+        let Ok((returned, state)) = tokio::task::spawn_blocking(move || {
+            let state = provider.state();
+            (provider, state)
+        })
+        .await
+        else {
+            activity::error("capture", "state provider panicked");
+            break;
+        };
+        provider = returned;
+
+        let config = config();
+        for rule in config.rules.iter() {
+            let config::Event::CaptureInUse(event_cfg) = &rule.event else {
+                continue;
+            };
+
+            if let Some(rule_in_use) = changes.update(&rule.name, event_cfg.device, &state) {
+                rule.send(&config, event_cfg.reports(rule_in_use));
+            }
+        }
+    }
+}
+
+async fn watch_workspaces() {
+    let mut rx = workspace::WORKSPACE_TX.subscribe();
+    loop {
+        if rx.changed().await.is_err() {
+            break;
+        }
+        let Some(workspace) = rx.borrow().clone() else {
+            continue;
+        };
+
+        let config = config();
+        for rule in config.rules.iter() {
+            if let config::Event::WorkspaceChanged(event_cfg) = &rule.event {
+                rule.send(&config, event_cfg.reports(&workspace));
+            }
+        }
+    }
+}
+
+async fn watch_layouts() {
+    let mut rx = layout::LAYOUT_TX.subscribe();
+    loop {
+        if rx.changed().await.is_err() {
+            break;
+        }
+        let Some(layout) = rx.borrow().clone() else {
+            continue;
+        };
+
+        let config = config();
+        for rule in config.rules.iter() {
+            if let config::Event::KeyboardLayoutChanged(event_cfg) = &rule.event {
+                rule.send(&config, event_cfg.reports(&layout));
+            }
+        }
+    }
+}
+
+async fn watch_players() {
+    if let Err(e) = media::watch_players().await {
+        activity::error("media::watch_players", e);
+    }
+}
+
+async fn watch_media() {
+    let mut rx = media::MEDIA_TX.subscribe();
+    let mut matches: HashMap<String, bool> = HashMap::new();
+    loop {
+        if rx.changed().await.is_err() {
+            break;
+        }
+        let media = rx.borrow().clone();
+
+        let config = config();
+        for rule in config.rules.iter() {
+            let config::Event::Media(event_cfg) = &rule.event else {
+                continue;
+            };
+
+            // Matching reports are resent on every change, e.g. a new
+            // track, so they can show the current one.
+            let is_match = event_cfg.is_match(&media);
+            if matches.insert(rule.name.clone(), is_match) != Some(is_match) || is_match {
+                rule.send(&config, event_cfg.reports(is_match));
+            }
+        }
+    }
+}
+
+async fn watch_clipboard() {
+    let mut rx = clipboard::CLIPBOARD_TX.subscribe();
+    let mut matches: HashMap<String, bool> = HashMap::new();
+    let mut reverts: HashMap<String, Instant> = HashMap::new();
+    loop {
+        // Wakes up for the earliest pending revert, if any.
+        let changed = match reverts.values().min() {
+            Some(&deadline) => {
+                tokio::time::timeout_at(tokio::time::Instant::from_std(deadline), rx.changed())
+                    .await
+                    .ok()
+            }
+            None => Some(rx.changed().await),
+        };
+
+        let config = config();
+        match changed {
+            Some(Err(_)) => break,
+            Some(Ok(())) => {
+                let content = rx.borrow().clone();
+                for rule in config.rules.iter() {
+                    let config::Event::Clipboard(event_cfg) = &rule.event else {
+                        continue;
+                    };
+
+                    // Every matching copy is reported, e.g. another secret.
+                    let is_match = event_cfg.is_match(&content);
+                    if matches.insert(rule.name.clone(), is_match) != Some(is_match) || is_match {
+                        rule.send(&config, event_cfg.reports(is_match));
+                    }
+                    match event_cfg.revert_after_secs {
+                        Some(secs) if is_match => {
+                            reverts.insert(
+                                rule.name.clone(),
+                                Instant::now() + Duration::from_secs(secs),
+                            );
+                        }
+                        _ => {
+                            reverts.remove(&rule.name);
+                        }
+                    }
+                }
+            }
+            None => {
+                let now = Instant::now();
+                for rule in config.rules.iter() {
+                    let config::Event::Clipboard(event_cfg) = &rule.event else {
+                        continue;
+                    };
+
+                    if reverts
+                        .get(&rule.name)
+                        .is_some_and(|&deadline| deadline <= now)
+                    {
+                        matches.insert(rule.name.clone(), false);
+                        rule.send(&config, event_cfg.reports(false));
+                    }
+                }
+                reverts.retain(|_, deadline| *deadline > now);
+            }
+        }
+    }
+}
+
+async fn watch_files() {
+    let (mut watcher, mut rx) = match filewatch::FileWatcher::start() {
+        Ok(started) => started,
+        Err(e) => {
+            activity::error("filewatch::FileWatcher::start", e);
+            return;
+        }
+    };
+    // The contents entry last sent per rule, absent until the file is read.
+    let mut contents_matched: HashMap<String, Option<usize>> = HashMap::new();
+    loop {
+        // Wakes up regularly to pick up paths of new or edited rules.
+        let event = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await;
+
+        let config = config();
+        let rules: Vec<(&config::Rule, &config::FileWatchConfig, PathBuf)> = config
+            .rules
+            .iter()
+            .filter_map(|rule| match &rule.event {
+                config::Event::FileWatch(event_cfg) => Some((rule, event_cfg, event_cfg.path())),
+                _ => None,
+            })
+            .collect();
+        let paths: Vec<PathBuf> = rules.iter().map(|(_, _, path)| path.clone()).collect();
+        watcher.set_paths(&paths);
+
+        for (rule, event_cfg, path) in rules.iter() {
+            if event_cfg.contents.is_empty() || contents_matched.contains_key(&rule.name) {
+                continue;
+            }
+            if let Ok(contents) = std::fs::read_to_string(path) {
+                let matched = event_cfg.contents_match(&contents);
+                contents_matched.insert(rule.name.clone(), matched);
+                rule.send(&config, event_cfg.contents_reports(matched));
+            }
+        }
+
+        let event = match event {
+            Ok(Some(event)) => event,
+            Ok(None) => break,
+            Err(_) => continue,
+        };
+        let contents = match event.change {
+            filewatch::FileChange::Delete => None,
+            _ => std::fs::read_to_string(&event.path).ok(),
+        };
+        for (rule, event_cfg, path) in rules.iter() {
+            if *path != event.path {
+                continue;
+            }
+
+            rule.send(&config, event_cfg.change_reports(event.change));
+            if event_cfg.contents.is_empty() {
+                continue;
+            }
+            match &contents {
+                Some(contents) => {
+                    let matched = event_cfg.contents_match(contents);
+                    if contents_matched.insert(rule.name.clone(), matched) != Some(matched) {
+                        rule.send(&config, event_cfg.contents_reports(matched));
+                    }
+                }
+                None => {
+                    contents_matched.remove(&rule.name);
+                }
+            }
+        }
+    }
+}
+
+async fn serve_rpc() {
+    let mut calls = match rpc::start_server() {
+        Ok(calls) => calls,
+        Err(e) => {
+            activity::error("rpc::start_server", e);
+            return;
+        }
+    };
+    while let Some(call) = calls.recv().await {
+        rpc::handle(call);
+    }
+}
+
+async fn serve_webhooks() {
+    // The port is read once, the token is checked against the current
+    // config on every request.
+    let Some(listener) = config().webhook.clone() else {
+        return;
+    };
+    let mut calls = match webhook::start_server(&listener) {
+        Ok(calls) => calls,
+        Err(e) => {
+            activity::error("webhook::start_server", e);
+            return;
+        }
+    };
+    while let Some(call) = calls.recv().await {
+        webhook::handle(call);
+    }
+}
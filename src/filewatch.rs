@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileChange {
+    // The file appeared.
+    Create,
+    // The file was written, or replaced by renaming another file over it.
+    Modify,
+    // The file disappeared.
+    Delete,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileEvent {
+    pub path: PathBuf,
+    pub change: FileChange,
+}
+
+// Expands a leading `~` to the home directory, e.g. `~/.focus`.
+pub fn expand_path(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix("~"), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            Path::new(&home).join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use inotify_watcher::FileWatcher;
+
+#[cfg(target_os = "linux")]
+mod inotify_watcher {
+    use anyhow::{Context, Result};
+    use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask, Watches};
+    use std::{
+        collections::{HashMap, HashSet},
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+    };
+    use tokio::sync::mpsc;
+
+    use super::{FileChange, FileEvent};
+
+    // Files are watched through their directory, so they can be created,
+    // deleted and atomically replaced.
+    const DIR_MASK: WatchMask = WatchMask::CREATE
+        .union(WatchMask::CLOSE_WRITE)
+        .union(WatchMask::DELETE)
+        .union(WatchMask::MOVED_FROM)
+        .union(WatchMask::MOVED_TO);
+
+    #[derive(Default)]
+    struct WatchState {
+        dirs: HashMap<WatchDescriptor, PathBuf>,
+        // Watched files and whether they currently exist.
+        files: HashMap<PathBuf, bool>,
+    }
+
+    pub struct FileWatcher {
+        watches: Watches,
+        state: Arc<Mutex<WatchState>>,
+    }
+
+    impl FileWatcher {
+        pub fn start() -> Result<(Self, mpsc::UnboundedReceiver<FileEvent>)> {
+            let mut inotify = Inotify::init().context("failed to initialize inotify")?;
+            let watches = inotify.watches();
+            let state = Arc::new(Mutex::new(WatchState::default()));
+            let (tx, rx) = mpsc::unbounded_channel();
+
+            let thread_state = state.clone();
+            std::thread::Builder::new()
+                .name("file-watcher".into())
+                .spawn(move || {
+                    let mut buffer = [0u8; 4096];
+                    loop {
+                        let events = match inotify.read_events_blocking(&mut buffer) {
+                            Ok(events) => events,
+                            Err(e) => {
+                                tracing::error!("file watcher: failed to read events: {}", e);
+                                return;
+                            }
+                        };
+
+                        let Ok(mut state) = thread_state.lock() else {
+                            return;
+                        };
+                        for event in events {
+                            let (Some(dir), Some(name)) = (state.dirs.get(&event.wd), event.name)
+                            else {
+                                continue;
+                            };
+                            let path = dir.join(name);
+                            let Some(exists) = state.files.get_mut(&path) else {
+                                continue;
+                            };
+
+                            let change = if event
+                                .mask
+                                .intersects(EventMask::DELETE | EventMask::MOVED_FROM)
+                            {
+                                FileChange::Delete
+                            } else if event.mask.contains(EventMask::CLOSE_WRITE) || *exists {
+                                FileChange::Modify
+                            } else {
+                                FileChange::Create
+                            };
+                            *exists = change != FileChange::Delete;
+
+                            if tx.send(FileEvent { path, change }).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                })
+                .context("failed to spawn file watcher thread")?;
+
+            Ok((Self { watches, state }, rx))
+        }
+
+        // Watches exactly these files, dropping any others.
+        pub fn set_paths(&mut self, paths: &[PathBuf]) {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+
+            let wanted: HashSet<&Path> = paths.iter().map(PathBuf::as_path).collect();
+            state
+                .files
+                .retain(|path, _| wanted.contains(path.as_path()));
+            for path in paths {
+                if !state.files.contains_key(path) {
+                    state.files.insert(path.clone(), path.exists());
+                }
+            }
+
+            let wanted_dirs: HashSet<PathBuf> = state
+                .files
+                .keys()
+                .filter_map(|path| path.parent().map(Path::to_path_buf))
+                .collect();
+            let watched_dirs: HashSet<PathBuf> = state.dirs.values().cloned().collect();
+            if wanted_dirs == watched_dirs {
+                return;
+            }
+
+            let unwanted: Vec<WatchDescriptor> = state
+                .dirs
+                .iter()
+                .filter(|(_, dir)| !wanted_dirs.contains(*dir))
+                .map(|(wd, _)| wd.clone())
+                .collect();
+            for wd in unwanted {
+                state.dirs.remove(&wd);
+                let _ = self.watches.remove(wd);
+            }
+            for dir in wanted_dirs.difference(&watched_dirs) {
+                match self.watches.add(dir, DIR_MASK) {
+                    Ok(wd) => {
+                        state.dirs.insert(wd, dir.clone());
+                    }
+                    // Retried on the next call, the directory may be created later.
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => {
+                        tracing::error!("file watcher: failed to watch {}: {}", dir.display(), e)
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Files are only watched with inotify on Linux.
+#[cfg(not(target_os = "linux"))]
+pub struct FileWatcher;
+
+#[cfg(not(target_os = "linux"))]
+impl FileWatcher {
+    pub fn start() -> anyhow::Result<(Self, tokio::sync::mpsc::UnboundedReceiver<FileEvent>)> {
+        anyhow::bail!("file watching is not supported on this platform")
+    }
+
+    pub fn set_paths(&mut self, _paths: &[PathBuf]) {}
+}
@@ -1,11 +1,12 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashMap,
     sync::{LazyLock, Mutex},
 };
 
-use super::config::Device;
+use super::config::{Device, DeviceTarget};
 use anyhow::{Context, Result};
 use hidapi::{DeviceInfo, HidApi};
+use serde::Serialize;
 
 pub static HID_DEVICES: LazyLock<Mutex<HidDevices>> = LazyLock::new(|| {
     let mut devices = HidDevices::new();
@@ -13,19 +14,39 @@ pub static HID_DEVICES: LazyLock<Mutex<HidDevices>> = LazyLock::new(|| {
     Mutex::new(devices)
 });
 
-static HID_API: LazyLock<HidApi> =
-    LazyLock::new(|| HidApi::new().expect("Failed to create HID API instance"));
+// Its device list only changes on `refresh_devices`, hence the lock.
+static HID_API: LazyLock<Mutex<HidApi>> =
+    LazyLock::new(|| Mutex::new(HidApi::new().expect("Failed to create HID API instance")));
 
-#[derive(Debug, Default, Clone, Eq, PartialEq)]
+// Enumerates devices again, so unplugged devices stop counting as connected
+// and newly plugged ones are found.
+pub fn refresh_devices() {
+    match HID_DEVICES.lock() {
+        Ok(mut devices) => {
+            devices.refresh();
+        }
+        Err(e) => tracing::error!("refresh_devices: failed to acquire lock: {}", e),
+    }
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize)]
 pub struct HidMetadata {
     pub vendor_id: u16,
     pub product_id: u16,
     pub manufacturer_string: String,
     pub product_string: String,
-    pub usages: HashSet<UsagePair>,
+    pub interfaces: Vec<HidInterface>,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Serialize)]
+pub struct HidInterface {
+    pub usage: UsagePair,
+    pub interface_number: i32,
+    pub serial_number: Option<String>,
+    pub path: String,
 }
 
-#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Serialize)]
 pub struct UsagePair {
     pub usage_page: u16,
     pub usage: u16,
@@ -47,7 +68,8 @@ pub struct HidDeviceKey {
 
 pub struct HidDevices {
     metadata_map: HashMap<HidMetadataKey, HidMetadata>,
-    device_info_map: HashMap<HidDeviceKey, DeviceInfo>,
+    // Identical devices share a key, so every match is kept.
+    device_info_map: HashMap<HidDeviceKey, Vec<DeviceInfo>>,
 }
 
 impl HidDevices {
@@ -60,9 +82,20 @@ impl HidDevices {
 
     pub fn refresh(&mut self) -> &mut Self {
         let mut metadata_map: HashMap<HidMetadataKey, HidMetadata> = HashMap::new();
-        let mut device_info_map: HashMap<HidDeviceKey, DeviceInfo> = HashMap::new();
+        let mut device_info_map: HashMap<HidDeviceKey, Vec<DeviceInfo>> = HashMap::new();
 
-        for device_info in HID_API.device_list() {
+        let mut api = match HID_API.lock() {
+            Ok(api) => api,
+            Err(e) => {
+                tracing::error!("HidDevices::refresh: failed to acquire lock: {}", e);
+                return self;
+            }
+        };
+        if let Err(e) = api.refresh_devices() {
+            tracing::error!("HidDevices::refresh: {}", e);
+        }
+
+        for device_info in api.device_list() {
             let metadata_key = HidMetadataKey {
                 vendor_id: device_info.vendor_id(),
                 product_id: device_info.product_id(),
@@ -77,11 +110,19 @@ impl HidDevices {
                         .unwrap_or_default()
                         .to_string(),
                     product_string: device_info.product_string().unwrap_or_default().to_string(),
-                    usages: HashSet::new(),
+                    interfaces: Vec::new(),
                 });
-            entry.usages.insert(UsagePair {
-                usage_page: device_info.usage_page(),
-                usage: device_info.usage(),
+            entry.interfaces.push(HidInterface {
+                usage: UsagePair {
+                    usage_page: device_info.usage_page(),
+                    usage: device_info.usage(),
+                },
+                interface_number: device_info.interface_number(),
+                serial_number: device_info
+                    .serial_number()
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string),
+                path: device_info.path().to_string_lossy().into_owned(),
             });
 
             let device_info_key = HidDeviceKey {
@@ -90,7 +131,10 @@ impl HidDevices {
                 usage_page: device_info.usage_page(),
                 usage: device_info.usage(),
             };
-            device_info_map.insert(device_info_key, device_info.clone());
+            device_info_map
+                .entry(device_info_key)
+                .or_default()
+                .push(device_info.clone());
         }
 
         self.metadata_map = metadata_map;
@@ -102,33 +146,92 @@ impl HidDevices {
         self.metadata_map.values().cloned().collect()
     }
 
-    pub fn get(&self, key: &HidDeviceKey) -> Option<&DeviceInfo> {
-        self.device_info_map.get(key)
+    pub fn get_all(&self, key: &HidDeviceKey) -> &[DeviceInfo] {
+        self.device_info_map
+            .get(key)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
     }
 }
 
 impl Device {
+    fn key(&self) -> HidDeviceKey {
+        HidDeviceKey {
+            vendor_id: self.vid,
+            product_id: self.pid,
+            usage_page: self.usage_page,
+            usage: self.usage,
+        }
+    }
+
+    fn matches(&self, device_info: &DeviceInfo) -> bool {
+        if let Some(serial_number) = &self.serial_number
+            && device_info.serial_number() != Some(serial_number.as_str())
+        {
+            return false;
+        }
+
+        if let Some(interface_number) = self.interface_number
+            && device_info.interface_number() != interface_number
+        {
+            return false;
+        }
+
+        if let Some(path) = &self.path
+            && device_info.path().to_string_lossy() != path.as_str()
+        {
+            return false;
+        }
+
+        true
+    }
+
+    pub fn resolve(&self) -> Result<Vec<DeviceInfo>> {
+        let hid_devices = HID_DEVICES
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Failed to acquire HID_DEVICES lock"))?;
+
+        let matches = hid_devices
+            .get_all(&self.key())
+            .iter()
+            .filter(|device_info| self.matches(device_info))
+            .cloned();
+
+        Ok(match self.target {
+            DeviceTarget::First => matches.take(1).collect(),
+            DeviceTarget::All => matches.collect(),
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.resolve()
+            .is_ok_and(|device_infos| !device_infos.is_empty())
+    }
+
+    // Returns the number of HID interfaces the report was written to.
     pub fn send_report(&self, report: &[u8]) -> Result<usize> {
-        let device_info = {
-            let hid_devices = HID_DEVICES
-                .lock()
-                .map_err(|_| anyhow::anyhow!("Failed to acquire HID_DEVICES lock"))?;
-
-            hid_devices
-                .get(&HidDeviceKey {
-                    vendor_id: self.vid,
-                    product_id: self.pid,
-                    usage_page: self.usage_page,
-                    usage: self.usage,
-                })
-                .cloned()
-                .context("Device not found in cache")?
-        };
+        let device_infos = self.resolve()?;
 
-        let hid_device = device_info
-            .open_device(&HID_API)
-            .context("Failed to open HID device")?;
+        if device_infos.is_empty() {
+            anyhow::bail!("Device not found in cache");
+        }
+
+        let mut result = Ok(device_infos.len());
+        for device_info in &device_infos {
+            if let Err(e) = self.write_report(device_info, report) {
+                result = Err(e.context(format!(
+                    "Failed to write to {}",
+                    device_info.path().to_string_lossy()
+                )));
+            }
+        }
+
+        result
+    }
 
+    // The bytes written to the device: the report id, then the report zero
+    // padded to the report length.
+    pub fn frame_report(&self, report: &[u8]) -> Result<Vec<u8>> {
         let report_length = self.report_length as usize;
 
         if report.len() > report_length {
@@ -148,9 +251,32 @@ impl Device {
         bytes_to_write[0] = self.report_id;
         let end = 1 + report.len();
         bytes_to_write[1..end].copy_from_slice(report);
+        Ok(bytes_to_write)
+    }
+
+    #[tracing::instrument(
+        name = "hid_write",
+        skip_all,
+        fields(
+            device = %self.name,
+            path = %device_info.path().to_string_lossy(),
+            len = report.len(),
+        )
+    )]
+    fn write_report(&self, device_info: &DeviceInfo, report: &[u8]) -> Result<usize> {
+        let bytes_to_write = self.frame_report(report)?;
+        let api = HID_API
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Failed to acquire HID_API lock"))?;
+        let hid_device = device_info
+            .open_device(&api)
+            .context("Failed to open HID device")?;
+        drop(api);
 
-        hid_device
+        let written = hid_device
             .write(&bytes_to_write)
-            .with_context(|| "Failed to write to device")
+            .with_context(|| "Failed to write to device")?;
+        tracing::trace!(written, "wrote report");
+        Ok(written)
     }
 }
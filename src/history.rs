@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use chrono::{Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::Write,
+    path::Path,
+    sync::{LazyLock, Mutex},
+};
+use tokio::sync::broadcast;
+
+use crate::{
+    activity::{ACTIVITY_TX, Activity},
+    config::HistoryConfig,
+    engine,
+};
+
+// The recent activity, e.g. to find out why a rule didn't send. Kept in
+// memory and optionally appended to a JSON lines file.
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    // RFC 3339 in local time.
+    pub time: String,
+    #[serde(flatten)]
+    pub activity: Activity,
+}
+
+// Narrows entries down to a rule and/or device, unset fields match anything.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct HistoryFilter {
+    #[serde(default)]
+    pub rule: Option<String>,
+    #[serde(default)]
+    pub device: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.rule
+            .as_deref()
+            .is_none_or(|rule| entry.activity.rule() == Some(rule))
+            && self
+                .device
+                .as_deref()
+                .is_none_or(|device| entry.activity.involves_device(device))
+    }
+}
+
+#[derive(Default)]
+struct History {
+    entries: VecDeque<HistoryEntry>,
+    // Lines in the history file, which is compacted once it holds twice the
+    // capacity.
+    lines_in_file: usize,
+}
+
+static HISTORY: LazyLock<Mutex<History>> = LazyLock::new(|| Mutex::new(History::default()));
+
+// Oldest first.
+pub fn entries(filter: &HistoryFilter) -> Vec<HistoryEntry> {
+    match HISTORY.lock() {
+        Ok(history) => history
+            .entries
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .cloned()
+            .collect(),
+        Err(e) => {
+            tracing::error!("history::entries: failed to acquire lock: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+pub fn to_json_lines(entries: &[HistoryEntry]) -> String {
+    entries
+        .iter()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+pub fn export(entries: &[HistoryEntry], path: &Path) -> Result<()> {
+    fs::write(path, to_json_lines(entries))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// Lines that don't parse, e.g. from a newer version, are skipped. Returns the
+// entries and the number of lines.
+fn read_file(path: &Path) -> Result<(Vec<HistoryEntry>, usize)> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let lines = contents.lines().count();
+    let entries = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    Ok((entries, lines))
+}
+
+fn append_to_file(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Failed to append to {}", path.display()))
+}
+
+fn record(history: &mut History, entry: HistoryEntry, settings: &HistoryConfig) {
+    let capacity = settings.capacity.max(1);
+
+    if let Some(path) = &settings.file {
+        let written = if history.lines_in_file >= capacity * 2 {
+            // Compacts the file down to what is kept in memory.
+            let kept: Vec<HistoryEntry> = history
+                .entries
+                .iter()
+                .skip((history.entries.len() + 1).saturating_sub(capacity))
+                .cloned()
+                .chain([entry.clone()])
+                .collect();
+            export(&kept, path).map(|_| kept.len())
+        } else {
+            append_to_file(path, &entry).map(|_| history.lines_in_file + 1)
+        };
+        match written {
+            Ok(lines) => history.lines_in_file = lines,
+            Err(e) => tracing::error!("history: {:#}", e),
+        }
+    }
+
+    history.entries.push_back(entry);
+    while history.entries.len() > capacity {
+        history.entries.pop_front();
+    }
+}
+
+// Records activity until the app exits, after restoring the history file.
+pub async fn run() {
+    let mut rx = ACTIVITY_TX.subscribe();
+
+    let settings = engine::config().history.clone().unwrap_or_default();
+    if let Some(path) = &settings.file {
+        match read_file(path) {
+            Ok((restored, lines)) => {
+                if let Ok(mut history) = HISTORY.lock() {
+                    let skip = restored.len().saturating_sub(settings.capacity.max(1));
+                    history.entries.extend(restored.into_iter().skip(skip));
+                    history.lines_in_file = lines;
+                }
+            }
+            Err(e) => tracing::error!("history: {:#}", e),
+        }
+    }
+
+    loop {
+        let activity = match rx.recv().await {
+            Ok(activity) => activity,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let entry = HistoryEntry {
+            time: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+            activity,
+        };
+
+        // The settings can change while running, e.g. a smaller capacity.
+        let settings = engine::config().history.clone().unwrap_or_default();
+        match HISTORY.lock() {
+            Ok(mut history) => record(&mut history, entry, &settings),
+            Err(e) => tracing::error!("history::run: failed to acquire lock: {}", e),
+        }
+    }
+}
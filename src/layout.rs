@@ -0,0 +1,215 @@
+use std::sync::LazyLock;
+use tokio::sync::watch;
+
+// The active keyboard layout, a locale name such as `en-US` on Windows or the
+// XKB group name such as `English (US)` on X11. None until first detected.
+pub static LAYOUT_TX: LazyLock<watch::Sender<Option<String>>> = LazyLock::new(|| {
+    let (tx, _rx) = watch::channel(None);
+    tx
+});
+
+pub fn get_layout() -> Option<String> {
+    LAYOUT_TX.borrow().clone()
+}
+
+fn set_layout(layout: String) {
+    LAYOUT_TX.send_if_modified(|current| {
+        if current.as_ref() == Some(&layout) {
+            false
+        } else {
+            *current = Some(layout);
+            true
+        }
+    });
+}
+
+#[cfg(windows)]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+// On Windows layout switches, e.g. Win+Space, are only sent to the focused
+// window, so its layout is polled on a background thread. It is also read
+// again right away when the foreground window changes, see
+// `win32::start_foreground_hook`.
+#[cfg(windows)]
+pub fn start_layout_watcher() {
+    let spawned = std::thread::Builder::new()
+        .name("layout-watcher".into())
+        .spawn(|| {
+            loop {
+                refresh_layout();
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+
+    if let Err(e) = spawned {
+        tracing::error!("start_layout_watcher: failed to spawn thread: {}", e);
+    }
+}
+
+#[cfg(windows)]
+pub fn refresh_layout() {
+    if let Some(layout) = LayoutSource.current() {
+        set_layout(layout);
+    }
+}
+
+// Waits for XKB group changes on a background thread.
+#[cfg(not(windows))]
+pub fn start_layout_watcher() {
+    let spawned = std::thread::Builder::new()
+        .name("layout-watcher".into())
+        .spawn(|| {
+            let mut source = match LayoutSource::new() {
+                Ok(source) => source,
+                Err(e) => {
+                    tracing::error!("start_layout_watcher: {}", e);
+                    return;
+                }
+            };
+
+            loop {
+                if let Some(layout) = source.current() {
+                    set_layout(layout);
+                }
+                if let Err(e) = source.wait_for_change() {
+                    tracing::error!("layout watcher: {}", e);
+                    return;
+                }
+            }
+        });
+
+    if let Err(e) = spawned {
+        tracing::error!("start_layout_watcher: failed to spawn thread: {}", e);
+    }
+}
+
+#[cfg(windows)]
+struct LayoutSource;
+
+#[cfg(windows)]
+impl LayoutSource {
+    fn current(&mut self) -> Option<String> {
+        use windows::Win32::{
+            Globalization::LCIDToLocaleName,
+            UI::{
+                Input::KeyboardAndMouse::GetKeyboardLayout,
+                WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId},
+            },
+        };
+
+        const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_invalid() {
+                return None;
+            }
+            let tid = GetWindowThreadProcessId(hwnd, None);
+            let hkl = GetKeyboardLayout(tid);
+            // The low word of the HKL is the input language.
+            let langid = (hkl.0 as usize & 0xffff) as u32;
+            if langid == 0 {
+                return None;
+            }
+
+            let mut buf = [0u16; LOCALE_NAME_MAX_LENGTH];
+            let n = LCIDToLocaleName(langid, Some(&mut buf), 0);
+            if n > 1 {
+                Some(String::from_utf16_lossy(&buf[..n as usize - 1]))
+            } else {
+                Some(format!("{:04x}", langid))
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct LayoutSource {
+    conn: x11rb::rust_connection::RustConnection,
+}
+
+#[cfg(target_os = "linux")]
+impl LayoutSource {
+    fn new() -> anyhow::Result<Self> {
+        use anyhow::Context;
+        use x11rb::protocol::xkb::{self, ConnectionExt};
+
+        let (conn, _screen) = x11rb::connect(None).context("failed to connect to X11")?;
+        conn.xkb_use_extension(1, 0)?
+            .reply()
+            .context("XKB extension unavailable")?;
+
+        // Group switches, and group renames when the layout list changes.
+        let details = xkb::SelectEventsAux::new()
+            .state_notify(xkb::SelectEventsAuxStateNotify {
+                affect_state: xkb::StatePart::GROUP_STATE,
+                state_details: xkb::StatePart::GROUP_STATE,
+            })
+            .names_notify(xkb::SelectEventsAuxNamesNotify {
+                affect_names: xkb::NameDetail::GROUP_NAMES,
+                names_details: xkb::NameDetail::GROUP_NAMES,
+            });
+        conn.xkb_select_events(
+            xkb::ID::USE_CORE_KBD.into(),
+            0u16.into(),
+            0u16.into(),
+            0u16.into(),
+            0u16.into(),
+            &details,
+        )?
+        .check()
+        .context("failed to select XKB events")?;
+        Ok(Self { conn })
+    }
+
+    fn wait_for_change(&mut self) -> anyhow::Result<()> {
+        use x11rb::{connection::Connection, protocol::Event};
+
+        loop {
+            match self.conn.wait_for_event()? {
+                Event::XkbStateNotify(_) | Event::XkbNamesNotify(_) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    fn current(&mut self) -> Option<String> {
+        use x11rb::protocol::{
+            xkb::{self, ConnectionExt as _},
+            xproto::ConnectionExt as _,
+        };
+
+        let device = xkb::ID::USE_CORE_KBD.into();
+        let state = self.conn.xkb_get_state(device).ok()?.reply().ok()?;
+        let group = u8::from(state.group) as usize;
+
+        let names = self
+            .conn
+            .xkb_get_names(device, xkb::NameDetail::GROUP_NAMES)
+            .ok()?
+            .reply()
+            .ok()?;
+        let atom = *names.value_list.groups?.get(group)?;
+
+        let name = self.conn.get_atom_name(atom).ok()?.reply().ok()?.name;
+        Some(String::from_utf8_lossy(&name).into_owned())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+struct LayoutSource;
+
+#[cfg(not(any(target_os = "linux", windows)))]
+impl LayoutSource {
+    fn new() -> anyhow::Result<Self> {
+        anyhow::bail!("keyboard layout detection is not supported on this platform")
+    }
+
+    fn current(&mut self) -> Option<String> {
+        None
+    }
+
+    fn wait_for_change(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{EnvFilter, filter::ParseError, fmt, prelude::*};
+
+use crate::config::LoggingConfig;
+
+// Logs go to stderr, and as JSON lines to daily rotated files in the
+// platform's log directory, e.g. ~/.local/state/locked-in/logs on Linux and
+// %LOCALAPPDATA%\locked-in\logs on Windows.
+
+pub fn log_dir() -> Result<PathBuf> {
+    let base = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .context("Failed to find the log directory")?;
+    Ok(base.join("locked-in").join("logs"))
+}
+
+pub fn parse_filter(level: &str) -> Result<EnvFilter, ParseError> {
+    EnvFilter::builder().parse(level)
+}
+
+fn file_appender(settings: &LoggingConfig) -> Result<RollingFileAppender> {
+    let dir = log_dir()?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("locked-in")
+        .filename_suffix("log")
+        .max_log_files(settings.max_files.max(1))
+        .build(&dir)
+        .with_context(|| format!("Failed to open a log file in {}", dir.display()))
+}
+
+// Installs the global subscriber and logs panics, before the config is
+// loaded. Without a usable log directory only stderr is logged to.
+pub fn init() {
+    let (settings, settings_error) = match LoggingConfig::read() {
+        Ok(settings) => (settings, None),
+        Err(e) => (LoggingConfig::default(), Some(e)),
+    };
+    let (filter, filter_error) = match std::env::var("RUST_LOG") {
+        Ok(level) => (EnvFilter::new(level), None),
+        Err(_) => match parse_filter(&settings.level) {
+            Ok(filter) => (filter, None),
+            Err(e) => (EnvFilter::new("info"), Some(e)),
+        },
+    };
+    let (file, file_error) = match file_appender(&settings) {
+        Ok(appender) => (Some(appender), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file.map(|file| fmt::layer().json().with_writer(file)))
+        .init();
+
+    if let Some(e) = settings_error {
+        tracing::warn!("Using the default logging settings: {:#}", e);
+    }
+    if let Some(e) = filter_error {
+        tracing::warn!("Invalid log level {}, using info: {}", settings.level, e);
+    }
+    if let Some(e) = file_error {
+        tracing::warn!("Logging to stderr only: {:#}", e);
+    }
+
+    std::panic::set_hook(Box::new(|info| {
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        tracing::error!("PANIC: {info}\nBACKTRACE:\n{backtrace}");
+    }));
+}
+
+// Opens the log directory in the file manager.
+pub fn open_log_dir() -> Result<()> {
+    let dir = log_dir()?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    #[cfg(windows)]
+    let opener = "explorer";
+    #[cfg(not(windows))]
+    let opener = "xdg-open";
+
+    std::process::Command::new(opener)
+        .arg(&dir)
+        .spawn()
+        .with_context(|| format!("Failed to run {opener}"))?;
+    Ok(())
+}
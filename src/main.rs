@@ -1,9 +1,29 @@
+mod activity;
+mod capture;
+mod cli;
+mod clipboard;
 mod components;
 mod config;
+mod engine;
+mod filewatch;
 mod hid;
+mod history;
+mod layout;
+mod logging;
+mod media;
+mod metrics;
+mod net;
+mod power;
+mod qmk;
+mod rpc;
+mod sequencer;
+mod simulate;
+mod template;
+mod webhook;
 mod win;
+mod workspace;
 
-use std::io::Write;
+use clap::Parser;
 
 use dioxus::{
     desktop::{
@@ -18,12 +38,16 @@ use dioxus::{
 };
 
 use crate::components::{
+    devices::Devices,
     dialog::Dialog,
     edit_rule::EditRule,
     events::{
         capture_focused_window::CaptureFocusedWindow,
         capture_focused_window_shortcut::CaptureFocusedWindowShortcut,
     },
+    groups::Groups,
+    history::History,
+    report_library::ReportLibrary,
     rules::Rules,
 };
 
@@ -34,30 +58,39 @@ const HEADER_SVG: Asset = asset!("/assets/header.svg");
 static FOCUSED_WINDOW_SIGNAL: GlobalSignal<win::WindowMetadata> =
     Signal::global(win::get_focused_window);
 
+// The UI's copy of the engine's config, see `engine::CONFIG_TX`.
 pub static CONFIG_SIGNAL: GlobalSignal<config::Config> =
-    Signal::global(|| config::Config::load().expect("Failed to load config"));
+    Signal::global(|| engine::config().clone());
 
-fn install_panic_log(mut log_path: std::path::PathBuf) {
-    log_path.push("panic.log");
+// Whether a headless instance was already running when the UI started, the UI
+// then only edits its config instead of evaluating rules itself.
+static ATTACHED_TO_DAEMON: GlobalSignal<bool> = Signal::global(rpc::daemon_running);
 
-    std::panic::set_hook(Box::new(move |info| {
-        let bt = std::backtrace::Backtrace::force_capture();
-        let mut f = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)
-            .ok();
+fn main() {
+    let cli = cli::Cli::parse();
+    if let Some(command) = cli.command {
+        std::process::exit(cli::run(command, cli.json));
+    }
 
-        if let Some(f) = f.as_mut() {
-            let _ = writeln!(f, "PANIC: {info}\nBACKTRACE:\n{bt}\n---\n");
-        }
-    }));
-}
+    logging::init();
 
-fn main() {
-    let _foreground_hook = win::start_foreground_hook();
+    if cli.headless {
+        if let Err(e) = engine::run_headless() {
+            tracing::error!("engine::run_headless: {:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    install_panic_log(".".into());
+    // Kept alive until the app exits. Without it focus rules never fire, the
+    // rest still works.
+    let _foreground_hook = match engine::start_watchers() {
+        Ok(hook) => Some(hook),
+        Err(e) => {
+            tracing::error!("engine::start_watchers: {:#}", e);
+            None
+        }
+    };
 
     dioxus::LaunchBuilder::desktop()
         .with_cfg(
@@ -88,11 +121,13 @@ fn App() -> Element {
         None,
     );
     let menu_item_toggle = MenuItem::with_id("toggle", "Toggle", true, None);
+    let menu_item_open_logs = MenuItem::with_id("open_logs", "Open Logs", true, None);
 
     menu.append_items(&[
         &menu_item_quit,
         &menu_item_catpure_focused_window,
         &menu_item_toggle,
+        &menu_item_open_logs,
     ])
     .unwrap();
 
@@ -111,7 +146,7 @@ fn App() -> Element {
             std::process::exit(0);
         }
         "capture_focused_window" => {
-            println!("Capture Focused Window");
+            tracing::debug!("capture focused window armed");
             capture_window_shortcut_armed.set(true);
             spawn(async move {
                 let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(15));
@@ -123,28 +158,43 @@ fn App() -> Element {
             });
         }
         "toggle" => {
-            println!("Toggle clicked");
+            tracing::debug!("toggle clicked");
+        }
+        "open_logs" => {
+            if let Err(e) = logging::open_log_dir() {
+                tracing::error!("logging::open_log_dir: {:#}", e);
+            }
         }
         _ => {
-            println!("To menu item found");
+            tracing::debug!("unknown menu item {}", event.id.0);
         }
     });
 
+    // Decided before the embedded engine could start listening itself.
+    let attached = ATTACHED_TO_DAEMON();
+
     use_future(move || async move {
-        let mut rx = win::FOCUSED_WINDOW_TX.subscribe();
-        loop {
-            if rx.changed().await.is_err() {
-                break;
-            }
-            let focused_window = rx.borrow().clone();
-            *FOCUSED_WINDOW_SIGNAL.write() = focused_window.clone();
+        if !attached {
+            engine::run().await;
+        }
+    });
 
-            let config = CONFIG_SIGNAL.read();
-            for rule in config.rules.iter() {
-                if let config::Event::FocusedWindowChanged(_) = rule.event {
-                    rule.trigger(&focused_window);
-                }
-            }
+    // Edits apply to the embedded engine right away.
+    use_effect(move || engine::set_config(CONFIG_SIGNAL.read().clone()));
+
+    use_future(move || async move {
+        let mut rx = engine::CONFIG_TX.subscribe();
+        while rx.changed().await.is_ok() {
+            let config = rx.borrow_and_update().clone();
+            *CONFIG_SIGNAL.write() = config;
+        }
+    });
+
+    use_future(move || async move {
+        let mut rx = win::FOCUSED_WINDOW_TX.subscribe();
+        while rx.changed().await.is_ok() {
+            let focused_window = rx.borrow_and_update().clone();
+            *FOCUSED_WINDOW_SIGNAL.write() = focused_window;
         }
     });
 
@@ -186,6 +236,10 @@ fn Main() -> Element {
         .unwrap_or("null".to_string());
 
     let mut show_edit_rule_modal = use_signal(|| false);
+    let mut show_devices_modal = use_signal(|| false);
+    let mut show_groups_modal = use_signal(|| false);
+    let mut show_reports_modal = use_signal(|| false);
+    let mut show_history_modal = use_signal(|| false);
 
     let mut rule_to_edit: Signal<Option<String>> = use_signal(|| None);
 
@@ -195,6 +249,9 @@ fn Main() -> Element {
         main {
             class: "container",
             h2 { "Window data: {focused_window_title} - {focused_window_class}" }
+            if ATTACHED_TO_DAEMON() {
+                small { "Attached to a running headless instance, saving the config reloads it there." }
+            }
             div {
                 button {
                     onclick: move |_| {
@@ -203,9 +260,30 @@ fn Main() -> Element {
                     },
                     "Add rule"
                 }
+                button {
+                    onclick: move |_| show_devices_modal.set(true),
+                    "Devices"
+                }
+                button {
+                    onclick: move |_| show_groups_modal.set(true),
+                    "Groups"
+                }
+                button {
+                    onclick: move |_| show_reports_modal.set(true),
+                    "Reports"
+                }
+                button {
+                    onclick: move |_| show_history_modal.set(true),
+                    "History"
+                }
                 button {
                     onclick: move |_| {
-                        let _ = CONFIG_SIGNAL.read().save();
+                        if CONFIG_SIGNAL.read().save().is_ok()
+                            && ATTACHED_TO_DAEMON()
+                            && let Err(e) = rpc::call("config.reload", serde_json::Value::Null)
+                        {
+                            tracing::error!("rpc::call: {}", e);
+                        }
                     },
                     "Save Config"
                 }
@@ -227,6 +305,38 @@ fn Main() -> Element {
                     }
                 },
             }
+            if show_devices_modal() {
+                Dialog {
+                    title: "Devices".to_string(),
+                    hide_buttons: true,
+                    on_cancel: move |_| show_devices_modal.set(false),
+                    Devices {}
+                }
+            }
+            if show_groups_modal() {
+                Dialog {
+                    title: "Groups".to_string(),
+                    hide_buttons: true,
+                    on_cancel: move |_| show_groups_modal.set(false),
+                    Groups {}
+                }
+            }
+            if show_reports_modal() {
+                Dialog {
+                    title: "Reports".to_string(),
+                    hide_buttons: true,
+                    on_cancel: move |_| show_reports_modal.set(false),
+                    ReportLibrary {}
+                }
+            }
+            if show_history_modal() {
+                Dialog {
+                    title: "History".to_string(),
+                    hide_buttons: true,
+                    on_cancel: move |_| show_history_modal.set(false),
+                    History {}
+                }
+            }
         }
     }
 }
@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tokio::sync::watch;
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+    Deserialize,
+    Serialize,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PlaybackStatus {
+    #[default]
+    Stopped,
+    Paused,
+    Playing,
+}
+
+// The most relevant media player: a playing one if any, else a paused one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MediaState {
+    // The player's MPRIS bus name without the prefix, e.g. `spotify`.
+    pub player: Option<String>,
+    pub status: PlaybackStatus,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+}
+
+pub static MEDIA_TX: LazyLock<watch::Sender<MediaState>> = LazyLock::new(|| {
+    let (tx, _rx) = watch::channel(MediaState::default());
+    tx
+});
+
+pub fn get_media() -> MediaState {
+    MEDIA_TX.borrow().clone()
+}
+
+#[cfg(target_os = "linux")]
+fn set_media(media: MediaState) {
+    MEDIA_TX.send_if_modified(|current| {
+        if *current == media {
+            false
+        } else {
+            *current = media;
+            true
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+pub use mpris::watch_players;
+
+#[cfg(not(target_os = "linux"))]
+pub async fn watch_players() -> anyhow::Result<()> {
+    anyhow::bail!("media players can only be watched over MPRIS on Linux")
+}
+
+#[cfg(target_os = "linux")]
+mod mpris {
+    use anyhow::Result;
+    use futures_util::StreamExt;
+    use std::{collections::HashMap, time::Duration};
+    use zbus::{
+        Connection, MatchRule, MessageStream,
+        fdo::DBusProxy,
+        message::Type,
+        proxy::CacheProperties,
+        zvariant::{OwnedValue, Value},
+    };
+
+    use super::{MediaState, PlaybackStatus, set_media};
+
+    const BUS_NAME_PREFIX: &str = "org.mpris.MediaPlayer2.";
+    const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+    const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+    // Catches anything the signals miss, e.g. a player crashing.
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+    // Watches MPRIS players on the session bus, re-reading every player
+    // whenever one changes, appears or goes away.
+    pub async fn watch_players() -> Result<()> {
+        let conn = Connection::session().await?;
+
+        let properties_changed = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface("org.freedesktop.DBus.Properties")?
+            .member("PropertiesChanged")?
+            .path(OBJECT_PATH)?
+            .build();
+        let owner_changed = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender("org.freedesktop.DBus")?
+            .interface("org.freedesktop.DBus")?
+            .member("NameOwnerChanged")?
+            .arg0ns("org.mpris.MediaPlayer2")?
+            .build();
+
+        let mut signals = futures_util::stream::select(
+            MessageStream::for_match_rule(properties_changed, &conn, None).await?,
+            MessageStream::for_match_rule(owner_changed, &conn, None).await?,
+        );
+
+        loop {
+            set_media(query_players(&conn).await?);
+
+            if let Ok(None) = tokio::time::timeout(REFRESH_INTERVAL, signals.next()).await {
+                anyhow::bail!("session bus connection closed");
+            }
+        }
+    }
+
+    async fn query_players(conn: &Connection) -> Result<MediaState> {
+        let names = DBusProxy::new(conn).await?.list_names().await?;
+
+        let mut best: Option<MediaState> = None;
+        for name in names {
+            let Some(player) = name.as_str().strip_prefix(BUS_NAME_PREFIX) else {
+                continue;
+            };
+            // A player that doesn't answer is skipped rather than failing
+            // the whole query.
+            let Ok(media) = query_player(conn, name.as_str(), player).await else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|best| media.status > best.status) {
+                best = Some(media);
+            }
+        }
+
+        Ok(best.unwrap_or_default())
+    }
+
+    async fn query_player(conn: &Connection, name: &str, player: &str) -> Result<MediaState> {
+        let proxy: zbus::Proxy = zbus::proxy::Builder::new(conn)
+            .destination(name)?
+            .path(OBJECT_PATH)?
+            .interface(PLAYER_INTERFACE)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+
+        let status = match proxy
+            .get_property::<String>("PlaybackStatus")
+            .await?
+            .as_str()
+        {
+            "Playing" => PlaybackStatus::Playing,
+            "Paused" => PlaybackStatus::Paused,
+            _ => PlaybackStatus::Stopped,
+        };
+        let metadata: HashMap<String, OwnedValue> =
+            proxy.get_property("Metadata").await.unwrap_or_default();
+        let field = |key: &str| {
+            metadata
+                .get(key)
+                .map(|value| strings(value).join(", "))
+                .filter(|value| !value.is_empty())
+        };
+
+        Ok(MediaState {
+            player: Some(player.to_string()),
+            status,
+            artist: field("xesam:artist"),
+            title: field("xesam:title"),
+            album: field("xesam:album"),
+        })
+    }
+
+    // Metadata values are strings or lists of strings, e.g. several artists.
+    fn strings(value: &Value) -> Vec<String> {
+        match value {
+            Value::Str(s) => vec![s.to_string()],
+            Value::Array(array) => array.iter().flat_map(strings).collect(),
+            Value::Value(value) => strings(value),
+            _ => Vec::new(),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::{
+            collections::HashMap,
+            io::{BufRead, BufReader},
+            process::{Child, Command, Stdio},
+        };
+        use zbus::{
+            Connection, connection, interface,
+            zvariant::{OwnedValue, Value},
+        };
+
+        use super::{BUS_NAME_PREFIX, OBJECT_PATH, query_players};
+        use crate::media::{MediaState, PlaybackStatus};
+
+        // A bus of its own, so the user's players don't show up.
+        struct PrivateBus {
+            daemon: Child,
+            address: String,
+        }
+
+        impl PrivateBus {
+            // None if dbus-daemon isn't installed.
+            fn start() -> Option<Self> {
+                let mut daemon = Command::new("dbus-daemon")
+                    .args(["--session", "--nofork", "--print-address=1"])
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .ok()?;
+                let mut address = String::new();
+                BufReader::new(daemon.stdout.take()?)
+                    .read_line(&mut address)
+                    .ok()?;
+                Some(Self {
+                    daemon,
+                    address: address.trim().to_string(),
+                })
+            }
+
+            async fn connect(&self) -> Connection {
+                connection::Builder::address(self.address.as_str())
+                    .unwrap()
+                    .build()
+                    .await
+                    .unwrap()
+            }
+
+            async fn add_player(&self, player: &str, fake: FakePlayer) -> Connection {
+                connection::Builder::address(self.address.as_str())
+                    .unwrap()
+                    .name(format!("{BUS_NAME_PREFIX}{player}"))
+                    .unwrap()
+                    .serve_at(OBJECT_PATH, fake)
+                    .unwrap()
+                    .build()
+                    .await
+                    .unwrap()
+            }
+        }
+
+        impl Drop for PrivateBus {
+            fn drop(&mut self) {
+                let _ = self.daemon.kill();
+                let _ = self.daemon.wait();
+            }
+        }
+
+        struct FakePlayer {
+            status: &'static str,
+            artists: Vec<&'static str>,
+            title: &'static str,
+        }
+
+        #[interface(name = "org.mpris.MediaPlayer2.Player")]
+        impl FakePlayer {
+            #[zbus(property)]
+            fn playback_status(&self) -> String {
+                self.status.to_string()
+            }
+
+            #[zbus(property)]
+            fn metadata(&self) -> HashMap<String, OwnedValue> {
+                HashMap::from([
+                    (
+                        "xesam:artist".to_string(),
+                        OwnedValue::try_from(Value::from(self.artists.clone())).unwrap(),
+                    ),
+                    (
+                        "xesam:title".to_string(),
+                        OwnedValue::try_from(Value::from(self.title)).unwrap(),
+                    ),
+                ])
+            }
+        }
+
+        #[tokio::test]
+        async fn prefers_the_playing_player() {
+            let Some(bus) = PrivateBus::start() else {
+                eprintln!("dbus-daemon not found, skipping");
+                return;
+            };
+            let _paused = bus
+                .add_player(
+                    "paused",
+                    FakePlayer {
+                        status: "Paused",
+                        artists: vec!["Someone"],
+                        title: "Paused song",
+                    },
+                )
+                .await;
+            let _playing = bus
+                .add_player(
+                    "playing",
+                    FakePlayer {
+                        status: "Playing",
+                        artists: vec!["A", "B"],
+                        title: "Song",
+                    },
+                )
+                .await;
+
+            let media = query_players(&bus.connect().await).await.unwrap();
+            assert_eq!(
+                media,
+                MediaState {
+                    player: Some("playing".to_string()),
+                    status: PlaybackStatus::Playing,
+                    artist: Some("A, B".to_string()),
+                    title: Some("Song".to_string()),
+                    album: None,
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn nothing_without_players() {
+            let Some(bus) = PrivateBus::start() else {
+                eprintln!("dbus-daemon not found, skipping");
+                return;
+            };
+            let media = query_players(&bus.connect().await).await.unwrap();
+            assert_eq!(media, MediaState::default());
+        }
+    }
+}
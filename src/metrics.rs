@@ -0,0 +1,199 @@
+use std::sync::{LazyLock, Mutex};
+#[cfg(target_os = "linux")]
+use std::time::Instant;
+
+// Latest system metrics, refreshed while any interval rule is configured.
+pub static METRICS: LazyLock<Mutex<Metrics>> = LazyLock::new(|| Mutex::new(Metrics::default()));
+
+static PROVIDER: LazyLock<Mutex<MetricsProvider>> =
+    LazyLock::new(|| Mutex::new(MetricsProvider::default()));
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    // Percentages, 0-100.
+    pub cpu: f64,
+    pub ram: f64,
+    pub disk: f64,
+    // KiB/s across every interface but loopback.
+    pub net_rx: f64,
+    pub net_tx: f64,
+}
+
+impl Metrics {
+    pub fn get(&self, name: &str) -> Option<f64> {
+        match name {
+            "cpu" => Some(self.cpu),
+            "ram" => Some(self.ram),
+            "disk" => Some(self.disk),
+            "net_rx" => Some(self.net_rx),
+            "net_tx" => Some(self.net_tx),
+            _ => None,
+        }
+    }
+}
+
+// CPU and network usage are rates, so the previous sample is kept around.
+#[derive(Default)]
+struct MetricsProvider {
+    #[cfg(target_os = "linux")]
+    cpu: Option<CpuTimes>,
+    #[cfg(target_os = "linux")]
+    net: Option<(NetBytes, Instant)>,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy)]
+struct NetBytes {
+    rx: u64,
+    tx: u64,
+}
+
+pub fn get_metrics() -> Metrics {
+    match METRICS.lock() {
+        Ok(guard) => *guard,
+        Err(e) => {
+            tracing::error!("get_metrics: failed to acquire lock: {}", e);
+            Metrics::default()
+        }
+    }
+}
+
+pub fn refresh() {
+    let metrics = match PROVIDER.lock() {
+        Ok(mut provider) => provider.sample(),
+        Err(e) => {
+            tracing::error!("refresh_metrics: failed to acquire lock: {}", e);
+            return;
+        }
+    };
+
+    match METRICS.lock() {
+        Ok(mut guard) => *guard = metrics,
+        Err(e) => tracing::error!("refresh_metrics: failed to acquire lock: {}", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl MetricsProvider {
+    fn sample(&mut self) -> Metrics {
+        let mut metrics = Metrics::default();
+
+        if let Some(cpu) = read_cpu_times() {
+            if let Some(prev) = self.cpu {
+                let total = cpu.total.saturating_sub(prev.total);
+                let idle = cpu.idle.saturating_sub(prev.idle);
+                if total > 0 {
+                    metrics.cpu = 100.0 * (total - idle.min(total)) as f64 / total as f64;
+                }
+            }
+            self.cpu = Some(cpu);
+        }
+
+        if let Some(ram) = read_ram_usage() {
+            metrics.ram = ram;
+        }
+
+        if let Some(disk) = read_disk_usage("/") {
+            metrics.disk = disk;
+        }
+
+        if let Some(net) = read_net_bytes() {
+            let now = Instant::now();
+            if let Some((prev, at)) = self.net {
+                let secs = now.duration_since(at).as_secs_f64();
+                if secs > 0.0 {
+                    metrics.net_rx = net.rx.saturating_sub(prev.rx) as f64 / 1024.0 / secs;
+                    metrics.net_tx = net.tx.saturating_sub(prev.tx) as f64 / 1024.0 / secs;
+                }
+            }
+            self.net = Some((net, now));
+        }
+
+        metrics
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl MetricsProvider {
+    fn sample(&mut self) -> Metrics {
+        Metrics::default()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_times() -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    // cpu  user nice system idle iowait irq softirq steal ...
+    let times: Vec<u64> = stat
+        .lines()
+        .next()?
+        .strip_prefix("cpu ")?
+        .split_whitespace()
+        .filter_map(|n| n.parse().ok())
+        .collect();
+
+    let idle = times.get(3)? + times.get(4).copied().unwrap_or_default();
+    let total = times.iter().take(8).sum();
+    Some(CpuTimes { idle, total })
+}
+
+#[cfg(target_os = "linux")]
+fn read_ram_usage() -> Option<f64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> Option<f64> {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(name))?
+            .trim_start_matches(':')
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    };
+
+    let total = field("MemTotal")?;
+    let available = field("MemAvailable")?;
+    (total > 0.0).then(|| 100.0 * (total - available) / total)
+}
+
+#[cfg(target_os = "linux")]
+fn read_disk_usage(path: &str) -> Option<f64> {
+    let path = std::ffi::CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let total = stat.f_blocks as f64;
+    let free = stat.f_bfree as f64;
+    (total > 0.0).then(|| 100.0 * (total - free) / total)
+}
+
+#[cfg(target_os = "linux")]
+fn read_net_bytes() -> Option<NetBytes> {
+    let dev = std::fs::read_to_string("/proc/net/dev").ok()?;
+    // Two header lines, then `iface: rx_bytes ... (8 rx fields) tx_bytes ...`
+    let mut bytes = NetBytes { rx: 0, tx: 0 };
+    for line in dev.lines().skip(2) {
+        let Some((iface, counters)) = line.split_once(':') else {
+            continue;
+        };
+        if iface.trim() == "lo" {
+            continue;
+        }
+        let counters: Vec<u64> = counters
+            .split_whitespace()
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        bytes.rx += counters.first().copied().unwrap_or_default();
+        bytes.tx += counters.get(8).copied().unwrap_or_default();
+    }
+    Some(bytes)
+}
@@ -0,0 +1,165 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetInterface {
+    pub name: String,
+    pub up: bool,
+}
+
+// Matches `name` against a pattern where `*` stands for any run of characters
+// and `?` for a single one, e.g. `tun*` or `wg?`.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_interfaces() -> Vec<NetInterface> {
+    const IFF_UP: u32 = 0x1;
+
+    let Ok(entries) = std::fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| {
+            let read = |name: &str| {
+                std::fs::read_to_string(entry.path().join(name))
+                    .map(|value| value.trim().to_string())
+                    .unwrap_or_default()
+            };
+
+            let flags =
+                u32::from_str_radix(read("flags").trim_start_matches("0x"), 16).unwrap_or_default();
+            // VPN tunnels report an "unknown" operstate while they are up.
+            let operstate = read("operstate");
+
+            NetInterface {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                up: flags & IFF_UP != 0 && matches!(operstate.as_str(), "up" | "unknown"),
+            }
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+pub fn get_interfaces() -> Vec<NetInterface> {
+    use windows::Win32::{
+        Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR},
+        NetworkManagement::{
+            IpHelper::{
+                GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+                GetAdaptersAddresses, IP_ADAPTER_ADDRESSES_LH,
+            },
+            Ndis::IfOperStatusUp,
+        },
+        Networking::WinSock::AF_UNSPEC,
+    };
+
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    let mut size = 16 * 1024u32;
+    // u64 keeps the buffer aligned for IP_ADAPTER_ADDRESSES_LH.
+    let mut buf: Vec<u64> = Vec::new();
+
+    let result = loop {
+        buf.resize((size as usize).div_ceil(8), 0);
+        let result = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC.0 as u32,
+                flags,
+                None,
+                Some(buf.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH),
+                &mut size,
+            )
+        };
+        if result != ERROR_BUFFER_OVERFLOW.0 {
+            break result;
+        }
+    };
+
+    if result != NO_ERROR.0 {
+        tracing::error!(
+            "get_interfaces: GetAdaptersAddresses failed with {}",
+            result
+        );
+        return Vec::new();
+    }
+
+    let mut interfaces = Vec::new();
+    let mut adapter = buf.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while let Some(current) = unsafe { adapter.as_ref() } {
+        if let Ok(name) = unsafe { current.FriendlyName.to_string() } {
+            interfaces.push(NetInterface {
+                name,
+                up: current.OperStatus == IfOperStatusUp,
+            });
+        }
+        adapter = current.Next;
+    }
+    interfaces
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn get_interfaces() -> Vec<NetInterface> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches_pattern;
+
+    #[test]
+    fn matches_literal_names() {
+        assert!(matches_pattern("eth0", "eth0"));
+        assert!(!matches_pattern("eth0", "eth01"));
+        assert!(!matches_pattern("eth0", "eth"));
+        assert!(matches_pattern("", ""));
+        assert!(!matches_pattern("", "eth0"));
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(matches_pattern("tun*", "tun0"));
+        assert!(matches_pattern("tun*", "tun"));
+        assert!(!matches_pattern("tun*", "wg0"));
+        assert!(matches_pattern("wg?", "wg0"));
+        assert!(!matches_pattern("wg?", "wg"));
+        assert!(!matches_pattern("wg?", "wg10"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("**", ""));
+    }
+
+    // A `*` gives back what it matched when the rest doesn't fit.
+    #[test]
+    fn backtracks_over_stars() {
+        assert!(matches_pattern("*vpn*", "my-vpn-vpn"));
+        assert!(matches_pattern("a*b*c", "aXbYbZc"));
+        assert!(!matches_pattern("a*b*c", "aXbYbZ"));
+        assert!(matches_pattern("*.?", "proton.vpn.x"));
+        assert!(matches_pattern("Ethernet *", "Ethernet 2"));
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PowerStatus {
+    pub on_ac: bool,
+    // None on machines without a battery.
+    pub battery_percent: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, strum_macros::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PowerState {
+    Ac,
+    Battery,
+    LowBattery,
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_power_status() -> PowerStatus {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return PowerStatus {
+            on_ac: true,
+            battery_percent: None,
+        };
+    };
+
+    let mut mains_online = None;
+    let mut discharging = false;
+    let mut capacities = Vec::new();
+
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let read = |name: &str| {
+            std::fs::read_to_string(path.join(name))
+                .map(|value| value.trim().to_string())
+                .ok()
+        };
+
+        // Wireless peripherals report their own batteries with a device scope.
+        if read("scope").as_deref() == Some("Device") {
+            continue;
+        }
+
+        match read("type").as_deref() {
+            Some("Mains") | Some("USB") => {
+                let online = read("online").as_deref() == Some("1");
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            }
+            Some("Battery") => {
+                if read("status").as_deref() == Some("Discharging") {
+                    discharging = true;
+                }
+                if let Some(capacity) = read("capacity").and_then(|c| c.parse::<u8>().ok()) {
+                    capacities.push(capacity.min(100));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let battery_percent = (!capacities.is_empty()).then(|| {
+        let sum: u32 = capacities.iter().map(|&c| c as u32).sum();
+        (sum / capacities.len() as u32) as u8
+    });
+
+    PowerStatus {
+        on_ac: mains_online.unwrap_or(!discharging),
+        battery_percent,
+    }
+}
+
+#[cfg(windows)]
+pub fn get_power_status() -> PowerStatus {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    const AC_OFFLINE: u8 = 0;
+    const NO_SYSTEM_BATTERY: u8 = 128;
+    const UNKNOWN_PERCENT: u8 = 255;
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    if let Err(e) = unsafe { GetSystemPowerStatus(&mut status) } {
+        tracing::error!("get_power_status: {}", e);
+        return PowerStatus {
+            on_ac: true,
+            battery_percent: None,
+        };
+    }
+
+    let has_battery =
+        status.BatteryFlag & NO_SYSTEM_BATTERY == 0 && status.BatteryLifePercent != UNKNOWN_PERCENT;
+
+    PowerStatus {
+        on_ac: status.ACLineStatus != AC_OFFLINE,
+        battery_percent: has_battery.then_some(status.BatteryLifePercent.min(100)),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn get_power_status() -> PowerStatus {
+    PowerStatus {
+        on_ac: true,
+        battery_percent: None,
+    }
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+
+// Size of a QMK raw HID report (RAW_EPSIZE), excluding the report id.
+pub const RAW_EPSIZE: usize = 32;
+
+// VIA command ids, see quantum/via.h.
+const ID_DYNAMIC_KEYMAP_GET_KEYCODE: u8 = 0x04;
+const ID_DYNAMIC_KEYMAP_SET_KEYCODE: u8 = 0x05;
+const ID_CUSTOM_SET_VALUE: u8 = 0x07;
+
+// VIA value ids shared by the rgblight and rgb matrix channels.
+const ID_QMK_RGB_BRIGHTNESS: u8 = 0x01;
+const ID_QMK_RGB_EFFECT: u8 = 0x02;
+const ID_QMK_RGB_COLOR: u8 = 0x04;
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RgbChannel {
+    #[default]
+    Rgblight,
+    RgbMatrix,
+}
+
+impl RgbChannel {
+    fn id(self) -> u8 {
+        match self {
+            Self::Rgblight => 0x02,
+            Self::RgbMatrix => 0x03,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(tag = "qmk", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Preset {
+    // Layer switching isn't part of VIA, the keyboard's `raw_hid_receive`
+    // handles `[command, layer]`.
+    SwitchLayer {
+        command: u8,
+        layer: u8,
+    },
+    RgbMode {
+        #[serde(default)]
+        channel: RgbChannel,
+        mode: u8,
+    },
+    RgbHsv {
+        #[serde(default)]
+        channel: RgbChannel,
+        hue: u8,
+        saturation: u8,
+        value: u8,
+    },
+    ViaGetKeycode {
+        layer: u8,
+        row: u8,
+        column: u8,
+    },
+    ViaSetKeycode {
+        layer: u8,
+        row: u8,
+        column: u8,
+        keycode: u16,
+    },
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Self::SwitchLayer {
+            command: 0,
+            layer: 0,
+        }
+    }
+}
+
+impl Preset {
+    // Encodes the preset into one or more RAW_EPSIZE reports.
+    pub fn encode(&self) -> Vec<Vec<u8>> {
+        let payloads = match *self {
+            Self::SwitchLayer { command, layer } => vec![vec![command, layer]],
+            Self::RgbMode { channel, mode } => {
+                vec![vec![
+                    ID_CUSTOM_SET_VALUE,
+                    channel.id(),
+                    ID_QMK_RGB_EFFECT,
+                    mode,
+                ]]
+            }
+            Self::RgbHsv {
+                channel,
+                hue,
+                saturation,
+                value,
+            } => vec![
+                vec![
+                    ID_CUSTOM_SET_VALUE,
+                    channel.id(),
+                    ID_QMK_RGB_COLOR,
+                    hue,
+                    saturation,
+                ],
+                vec![
+                    ID_CUSTOM_SET_VALUE,
+                    channel.id(),
+                    ID_QMK_RGB_BRIGHTNESS,
+                    value,
+                ],
+            ],
+            Self::ViaGetKeycode { layer, row, column } => {
+                vec![vec![ID_DYNAMIC_KEYMAP_GET_KEYCODE, layer, row, column]]
+            }
+            Self::ViaSetKeycode {
+                layer,
+                row,
+                column,
+                keycode,
+            } => {
+                let [hi, lo] = keycode.to_be_bytes();
+                vec![vec![
+                    ID_DYNAMIC_KEYMAP_SET_KEYCODE,
+                    layer,
+                    row,
+                    column,
+                    hi,
+                    lo,
+                ]]
+            }
+        };
+
+        payloads
+            .into_iter()
+            .map(|mut payload| {
+                payload.resize(RAW_EPSIZE, 0);
+                payload
+            })
+            .collect()
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::SwitchLayer { command, layer } => {
+                format!("Switch to layer {layer} (command {command})")
+            }
+            Self::RgbMode { channel, mode } => format!("Set {channel} mode {mode}"),
+            Self::RgbHsv {
+                channel,
+                hue,
+                saturation,
+                value,
+            } => format!("Set {channel} HSV {hue}, {saturation}, {value}"),
+            Self::ViaGetKeycode { layer, row, column } => {
+                format!("Get keycode at layer {layer}, row {row}, column {column}")
+            }
+            Self::ViaSetKeycode {
+                layer,
+                row,
+                column,
+                keycode,
+            } => format!("Set keycode {keycode:#06x} at layer {layer}, row {row}, column {column}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded(payload: &[u8]) -> Vec<u8> {
+        let mut report = payload.to_vec();
+        report.resize(RAW_EPSIZE, 0);
+        report
+    }
+
+    #[test]
+    fn encodes_layer_switches() {
+        let preset = Preset::SwitchLayer {
+            command: 0x10,
+            layer: 3,
+        };
+        assert_eq!(preset.encode(), [padded(&[0x10, 3])]);
+    }
+
+    #[test]
+    fn encodes_rgb_presets_per_channel() {
+        let mode = Preset::RgbMode {
+            channel: RgbChannel::RgbMatrix,
+            mode: 5,
+        };
+        assert_eq!(mode.encode(), [padded(&[0x07, 0x03, 0x02, 5])]);
+
+        // Color and brightness are separate VIA values.
+        let hsv = Preset::RgbHsv {
+            channel: RgbChannel::Rgblight,
+            hue: 10,
+            saturation: 20,
+            value: 30,
+        };
+        assert_eq!(
+            hsv.encode(),
+            [
+                padded(&[0x07, 0x02, 0x04, 10, 20]),
+                padded(&[0x07, 0x02, 0x01, 30])
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_keycodes_big_endian() {
+        let get = Preset::ViaGetKeycode {
+            layer: 1,
+            row: 2,
+            column: 3,
+        };
+        assert_eq!(get.encode(), [padded(&[0x04, 1, 2, 3])]);
+
+        let set = Preset::ViaSetKeycode {
+            layer: 1,
+            row: 2,
+            column: 3,
+            keycode: 0x5220,
+        };
+        assert_eq!(set.encode(), [padded(&[0x05, 1, 2, 3, 0x52, 0x20])]);
+    }
+
+    #[test]
+    fn deserializes_with_the_default_channel() {
+        let preset: Preset = toml::from_str("qmk = \"rgb_mode\"\nmode = 7").unwrap();
+        assert_eq!(
+            preset,
+            Preset::RgbMode {
+                channel: RgbChannel::Rgblight,
+                mode: 7
+            }
+        );
+    }
+}
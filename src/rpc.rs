@@ -0,0 +1,550 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use crate::{
+    activity::ACTIVITY_TX,
+    config::{Config, Rule, RuleSummary},
+    engine,
+    history::{self, HistoryFilter},
+    win,
+};
+
+// JSON-RPC 2.0 over a local socket, one message per line. Only the current
+// user can connect: a Unix socket in a private directory on Linux, a named
+// pipe owned by the user on Windows.
+//
+// Methods:
+// - rules.list
+// - config.reload, e.g. after the UI saved the config file
+// - rules.enable / rules.disable {"name"}
+// - rules.fire {"name", "list"?}, sends one of the rule's report lists, the
+//   first by default, in the order the rule's event lists them
+// - devices.send {"device", "hex"}, sends raw bytes to a registered device
+// - window.focused
+// - events.subscribe, then `event` notifications follow
+// - history.list {"rule"?, "device"?}, the recorded events, oldest first
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Serialize)]
+pub struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+// A request needing the config, answered by `handle` inside the engine.
+pub struct RpcCall {
+    method: String,
+    params: Value,
+    reply: oneshot::Sender<Result<Value, RpcError>>,
+}
+
+#[derive(Deserialize)]
+struct RuleParams {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct FireParams {
+    name: String,
+    #[serde(default)]
+    list: usize,
+}
+
+#[derive(Deserialize)]
+struct SendParams {
+    device: String,
+    hex: String,
+}
+
+fn params<T: DeserializeOwned>(params: &Value) -> Result<T, RpcError> {
+    serde_json::from_value(params.clone()).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn find_rule<'a>(config: &'a Config, name: &str) -> Result<&'a Rule, RpcError> {
+    config
+        .get_rule(name)
+        .ok_or_else(|| RpcError::new(SERVER_ERROR, format!("unknown rule {name}")))
+}
+
+pub fn handle(call: RpcCall) {
+    let result = match call.method.as_str() {
+        "rules.list" => {
+            let rules: Vec<RuleSummary> =
+                engine::config().rules.iter().map(Rule::summary).collect();
+            Ok(json!(rules))
+        }
+        "rules.enable" | "rules.disable" => params::<RuleParams>(&call.params).and_then(|p| {
+            find_rule(&engine::config(), &p.name)?;
+            let disabled = call.method == "rules.disable";
+            engine::update_config(|config| {
+                if let Some(rule) = config.get_mut_rule(&p.name) {
+                    rule.disabled = disabled;
+                }
+            });
+            Ok(Value::Null)
+        }),
+        "config.reload" => Config::load()
+            .map(|config| {
+                engine::update_config(|current| *current = config);
+                Value::Null
+            })
+            .map_err(|e| RpcError::new(SERVER_ERROR, format!("{e:#}"))),
+        "rules.fire" => params::<FireParams>(&call.params).and_then(|p| {
+            let config = engine::config();
+            let rule = find_rule(&config, &p.name)?;
+            if rule.disabled {
+                return Err(RpcError::new(
+                    SERVER_ERROR,
+                    format!("rule {} is disabled", p.name),
+                ));
+            }
+            let lists = rule.event.report_lists();
+            let reports = lists.get(p.list).ok_or_else(|| {
+                RpcError::new(
+                    INVALID_PARAMS,
+                    format!("rule {} has {} report lists", p.name, lists.len()),
+                )
+            })?;
+            rule.send(&config, reports);
+            Ok(Value::Null)
+        }),
+        "devices.send" => params::<SendParams>(&call.params).and_then(|p| {
+            let config = engine::config();
+            let device = config.get_device(&p.device).ok_or_else(|| {
+                RpcError::new(SERVER_ERROR, format!("unknown device {}", p.device))
+            })?;
+            let hex_digits: String = p.hex.split_whitespace().collect();
+            let bytes = hex::decode(&hex_digits)
+                .map_err(|e| RpcError::new(INVALID_PARAMS, format!("invalid hex bytes: {e}")))?;
+            let written = device
+                .send_report(&bytes)
+                .map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+            Ok(json!(written))
+        }),
+        "history.list" => {
+            let filter = if call.params.is_null() {
+                Ok(HistoryFilter::default())
+            } else {
+                params::<HistoryFilter>(&call.params)
+            };
+            filter.map(|filter| json!(history::entries(&filter)))
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method {other}"),
+        )),
+    };
+
+    let _ = call.reply.send(result);
+}
+
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+fn write_message(writer: &SharedWriter, message: &Value) -> std::io::Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    let mut writer = writer
+        .lock()
+        .map_err(|_| std::io::Error::other("writer lock poisoned"))?;
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+fn subscribe(writer: SharedWriter) {
+    let mut rx = ACTIVITY_TX.subscribe();
+    let spawned = std::thread::Builder::new()
+        .name("rpc-subscription".into())
+        .spawn(move || {
+            loop {
+                let activity = match rx.blocking_recv() {
+                    Ok(activity) => activity,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "event",
+                    "params": activity,
+                });
+                if write_message(&writer, &notification).is_err() {
+                    return;
+                }
+            }
+        });
+
+    if let Err(e) = spawned {
+        tracing::error!("rpc: failed to spawn subscription thread: {}", e);
+    }
+}
+
+fn handle_connection(
+    reader: impl Read,
+    writer: impl Write + Send + 'static,
+    calls: mpsc::UnboundedSender<RpcCall>,
+) {
+    let writer: SharedWriter = Arc::new(Mutex::new(Box::new(writer)));
+    let mut subscribed = false;
+
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (id, result) = match serde_json::from_str::<Value>(&line) {
+            Err(e) => (Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string()))),
+            Ok(message) => match serde_json::from_value::<Request>(message) {
+                Err(e) => (
+                    Value::Null,
+                    Err(RpcError::new(INVALID_REQUEST, e.to_string())),
+                ),
+                Ok(request) => {
+                    let result = match request.method.as_str() {
+                        "window.focused" => Ok(json!(win::get_focused_window())),
+                        "events.subscribe" => {
+                            if !subscribed {
+                                subscribed = true;
+                                subscribe(writer.clone());
+                            }
+                            Ok(Value::Null)
+                        }
+                        _ => {
+                            let (reply, reply_rx) = oneshot::channel();
+                            let call = RpcCall {
+                                method: request.method,
+                                params: request.params,
+                                reply,
+                            };
+                            if calls.send(call).is_err() {
+                                return;
+                            }
+                            reply_rx.blocking_recv().unwrap_or_else(|_| {
+                                Err(RpcError::new(SERVER_ERROR, "request dropped"))
+                            })
+                        }
+                    };
+                    (request.id, result)
+                }
+            },
+        };
+
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        };
+        if write_message(&writer, &response).is_err() {
+            return;
+        }
+    }
+}
+
+fn spawn_connection(
+    reader: impl Read + Send + 'static,
+    writer: impl Write + Send + 'static,
+    calls: &mpsc::UnboundedSender<RpcCall>,
+) {
+    let calls = calls.clone();
+    let spawned = std::thread::Builder::new()
+        .name("rpc-connection".into())
+        .spawn(move || handle_connection(reader, writer, calls));
+
+    if let Err(e) = spawned {
+        tracing::error!("rpc: failed to spawn connection thread: {}", e);
+    }
+}
+
+// Listens on the control socket, requests needing the config come out of the
+// returned channel and must be passed to `handle`.
+pub fn start_server() -> Result<mpsc::UnboundedReceiver<RpcCall>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    listener::start(tx)?;
+    Ok(rx)
+}
+
+// Sends a single request to the instance listening on the control socket and
+// returns its result.
+pub fn call(method: &str, params: Value) -> Result<Value> {
+    let (reader, mut writer) = listener::connect()?;
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    writeln!(writer, "{request}").context("failed to send request")?;
+    writer.flush().context("failed to send request")?;
+
+    let mut line = String::new();
+    BufReader::new(reader)
+        .read_line(&mut line)
+        .context("failed to read response")?;
+    let mut response: Value = serde_json::from_str(&line).context("invalid response")?;
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("request failed");
+        anyhow::bail!("{method}: {message}");
+    }
+    Ok(response["result"].take())
+}
+
+// Whether another instance, e.g. a headless one, is listening.
+pub fn daemon_running() -> bool {
+    listener::connect().is_ok()
+}
+
+#[cfg(target_os = "linux")]
+mod listener {
+    use anyhow::{Context, Result};
+    use std::{
+        fs,
+        os::unix::{
+            fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+            net::{UnixListener, UnixStream},
+        },
+        path::PathBuf,
+    };
+    use tokio::sync::mpsc;
+
+    use super::{RpcCall, spawn_connection};
+
+    // $XDG_RUNTIME_DIR is private to the user already, the fallback directory
+    // is created private.
+    pub fn socket_path() -> Result<PathBuf> {
+        if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+            return Ok(PathBuf::from(runtime_dir).join("lockedin.sock"));
+        }
+
+        let uid = unsafe { libc::getuid() };
+        let dir = std::env::temp_dir().join(format!("lockedin-{uid}"));
+        if !dir.exists() {
+            fs::DirBuilder::new()
+                .mode(0o700)
+                .create(&dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let metadata = fs::metadata(&dir)?;
+        anyhow::ensure!(
+            metadata.uid() == uid && metadata.mode() & 0o077 == 0,
+            "{} is not private to the current user",
+            dir.display()
+        );
+        Ok(dir.join("lockedin.sock"))
+    }
+
+    pub fn connect() -> Result<(UnixStream, UnixStream)> {
+        let path = socket_path()?;
+        let stream = UnixStream::connect(&path)
+            .with_context(|| format!("failed to connect to {}", path.display()))?;
+        Ok((stream.try_clone()?, stream))
+    }
+
+    pub fn start(calls: mpsc::UnboundedSender<RpcCall>) -> Result<()> {
+        let path = socket_path()?;
+        if path.exists() {
+            anyhow::ensure!(
+                UnixStream::connect(&path).is_err(),
+                "another instance is listening on {}",
+                path.display()
+            );
+            // Left over from a previous run.
+            fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("failed to listen on {}", path.display()))?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+        std::thread::Builder::new()
+            .name("rpc-listener".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            tracing::error!("rpc: failed to accept connection: {}", e);
+                            continue;
+                        }
+                    };
+                    match stream.try_clone() {
+                        Ok(reader) => spawn_connection(reader, stream, &calls),
+                        Err(e) => tracing::error!("rpc: failed to clone connection: {}", e),
+                    }
+                }
+            })
+            .context("failed to spawn rpc listener thread")?;
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod listener {
+    use anyhow::{Context, Result};
+    use std::{
+        fs::File,
+        os::windows::io::{AsRawHandle, FromRawHandle, RawHandle},
+    };
+    use tokio::sync::mpsc;
+    use windows::{
+        Win32::{
+            Foundation::{ERROR_PIPE_CONNECTED, HANDLE},
+            Security::{
+                Authorization::{
+                    ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+                },
+                PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES,
+            },
+            Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+            System::Pipes::{
+                ConnectNamedPipe, CreateNamedPipeW, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE,
+                PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+            },
+        },
+        core::{HSTRING, w},
+    };
+
+    use super::{RpcCall, spawn_connection};
+
+    const BUFFER_SIZE: u32 = 4096;
+
+    pub fn pipe_name() -> String {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        format!(r"\\.\pipe\lockedin-{user}")
+    }
+
+    pub fn connect() -> Result<(File, File)> {
+        let pipe = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(pipe_name())
+            .with_context(|| format!("failed to connect to {}", pipe_name()))?;
+        Ok((pipe.try_clone()?, pipe))
+    }
+
+    // Only the pipe's owner, the current user, gets access.
+    struct PipeSecurity(PSECURITY_DESCRIPTOR);
+    // The descriptor is only read after creation.
+    unsafe impl Send for PipeSecurity {}
+
+    fn create_instance(name: &HSTRING, security: &PipeSecurity, first: bool) -> Result<File> {
+        let attributes = SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: security.0.0,
+            bInheritHandle: false.into(),
+        };
+        let mut open_mode = PIPE_ACCESS_DUPLEX;
+        if first {
+            // Fails if someone else already created the pipe.
+            open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name,
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                Some(&attributes),
+            )
+        };
+        anyhow::ensure!(
+            !handle.is_invalid(),
+            "failed to create pipe: {}",
+            windows::core::Error::from_thread()
+        );
+        // The file closes the handle once the connection ends.
+        Ok(unsafe { File::from_raw_handle(handle.0 as RawHandle) })
+    }
+
+    pub fn start(calls: mpsc::UnboundedSender<RpcCall>) -> Result<()> {
+        let name = HSTRING::from(pipe_name());
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                w!("D:P(A;;GA;;;OW)"),
+                SDDL_REVISION_1,
+                &mut descriptor,
+                None,
+            )?;
+        }
+        let security = PipeSecurity(descriptor);
+
+        let mut pipe = create_instance(&name, &security, true)
+            .with_context(|| format!("failed to listen on {name}"))?;
+
+        std::thread::Builder::new()
+            .name("rpc-listener".into())
+            .spawn(move || {
+                loop {
+                    let handle = HANDLE(pipe.as_raw_handle());
+                    let connected = match unsafe { ConnectNamedPipe(handle, None) } {
+                        Ok(()) => true,
+                        // The client connected between creation and this call.
+                        Err(e) => e.code() == ERROR_PIPE_CONNECTED.to_hresult(),
+                    };
+                    let next = create_instance(&name, &security, false);
+                    if connected {
+                        match pipe.try_clone() {
+                            Ok(reader) => spawn_connection(reader, pipe, &calls),
+                            Err(e) => tracing::error!("rpc: failed to clone connection: {}", e),
+                        }
+                    }
+
+                    pipe = match next {
+                        Ok(pipe) => pipe,
+                        Err(e) => {
+                            tracing::error!("rpc: {}", e);
+                            return;
+                        }
+                    };
+                }
+            })
+            .context("failed to spawn rpc listener thread")?;
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod listener {
+    use tokio::sync::mpsc;
+
+    use super::RpcCall;
+
+    pub fn connect() -> anyhow::Result<(std::fs::File, std::fs::File)> {
+        anyhow::bail!("the control socket is not supported on this platform")
+    }
+
+    pub fn start(_calls: mpsc::UnboundedSender<RpcCall>) -> anyhow::Result<()> {
+        anyhow::bail!("the control socket is not supported on this platform")
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use serde_json::Value;
+use tokio::task::AbortHandle;
+use tracing::Instrument;
+
+use crate::{
+    activity::{self, Activity},
+    config::Device,
+    template::{Template, TemplateContext},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportAction {
+    Send(Vec<u8>),
+    // Rendered right before it is sent, so runtime values are current.
+    SendTemplate(Template),
+    Wait(Duration),
+}
+
+// Running sequences keyed by rule and device name, so a newer trigger of the
+// same rule on the same device cancels the one still in flight.
+static RUNNING: LazyLock<Mutex<HashMap<(String, String), AbortHandle>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn send(rule_name: &str, device: &Device, bytes: &[u8]) {
+    if let Err(e) = device.send_report(bytes) {
+        tracing::error!("Failed to send report to device {}: {}", device.name, e);
+        activity::publish(Activity::ReportFailed {
+            rule: rule_name.to_string(),
+            device: device.name.clone(),
+            error: format!("{e:#}"),
+        });
+    }
+}
+
+async fn run_actions(rule_name: String, device: Device, actions: Vec<ReportAction>, body: Value) {
+    for action in actions {
+        match action {
+            ReportAction::Send(bytes) => send(&rule_name, &device, &bytes),
+            ReportAction::SendTemplate(template) => send(
+                &rule_name,
+                &device,
+                &template.render(&TemplateContext::with_body(body.clone())),
+            ),
+            ReportAction::Wait(duration) => tokio::time::sleep(duration).await,
+        }
+    }
+}
+
+// Forgets the finished sequence, unless a newer one already replaced it.
+fn finish(key: &(String, String)) {
+    match RUNNING.lock() {
+        Ok(mut running) => {
+            if running
+                .get(key)
+                .is_some_and(|handle| handle.id() == tokio::task::id())
+            {
+                running.remove(key);
+            }
+        }
+        Err(e) => tracing::error!("sequencer::finish: failed to acquire lock: {}", e),
+    }
+}
+
+// Runs the actions for a device without blocking the caller, cancelling any
+// sequence the rule still has running on that device. `body` is the webhook
+// request's, for templates.
+pub fn run(rule_name: &str, device: Device, actions: Vec<ReportAction>, body: Value) {
+    let key = (rule_name.to_string(), device.name.clone());
+
+    let mut running = match RUNNING.lock() {
+        Ok(guard) => guard,
+        Err(e) => {
+            tracing::error!("sequencer::run: failed to acquire lock: {}", e);
+            return;
+        }
+    };
+
+    if let Some(handle) = running.remove(&key) {
+        handle.abort();
+    }
+
+    if actions.is_empty() {
+        return;
+    }
+
+    // Outside of an async runtime (e.g. from the command line) there is
+    // nothing to run alongside, so just block.
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        drop(running);
+        for action in actions {
+            match action {
+                ReportAction::Send(bytes) => send(rule_name, &device, &bytes),
+                ReportAction::SendTemplate(template) => send(
+                    rule_name,
+                    &device,
+                    &template.render(&TemplateContext::with_body(body.clone())),
+                ),
+                ReportAction::Wait(duration) => std::thread::sleep(duration),
+            }
+        }
+        return;
+    };
+
+    // Keeps the caller's rule span, so writes are logged under it.
+    let span = tracing::info_span!("sequence", device = %device.name);
+    let task_key = key.clone();
+    let task = runtime.spawn(
+        async move {
+            run_actions(task_key.0.clone(), device, actions, body).await;
+            // Waits for the insert below, the lock is held until then.
+            finish(&task_key);
+        }
+        .instrument(span),
+    );
+    running.insert(key, task.abort_handle());
+}
@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+use crate::{
+    config::{Config, Device, Event, Report, Rule},
+    sequencer::ReportAction,
+    template::TemplateContext,
+    win::WindowMetadata,
+};
+
+// Dry runs of rules: what they would write to their devices, without writing.
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SimulatedAction {
+    // The bytes written, with the report id and padding.
+    Write { bytes: Vec<u8> },
+    Wait { ms: u64 },
+    // The report can't be written, e.g. it is longer than the report length.
+    Error { message: String },
+}
+
+impl fmt::Display for SimulatedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Write { bytes } => {
+                let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+                write!(f, "{}", hex.join(" "))
+            }
+            Self::Wait { ms } => write!(f, "wait {ms} ms"),
+            Self::Error { message } => write!(f, "error: {message}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceSimulation {
+    pub device: String,
+    pub connected: bool,
+    pub actions: Vec<SimulatedAction>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowSimulation {
+    pub window: WindowMetadata,
+    pub devices: Vec<DeviceSimulation>,
+}
+
+fn frame(device: &Device, report: &[u8]) -> SimulatedAction {
+    match device.frame_report(report) {
+        Ok(bytes) => SimulatedAction::Write { bytes },
+        Err(e) => SimulatedAction::Error {
+            message: format!("{e:#}"),
+        },
+    }
+}
+
+// What sending the reports would write to each of the rule's devices, with
+// templates rendered from `context`. Disconnected group members are included.
+pub fn simulate_reports(
+    config: &Config,
+    rule: &Rule,
+    reports: &[Report],
+    context: &TemplateContext,
+) -> Vec<DeviceSimulation> {
+    config
+        .resolve_all_devices(rule)
+        .into_iter()
+        .map(|device| {
+            let actions = reports
+                .iter()
+                .flat_map(|report| report.actions(config, &device))
+                .map(|action| match action {
+                    ReportAction::Send(bytes) => frame(&device, &bytes),
+                    ReportAction::SendTemplate(template) => {
+                        frame(&device, &template.render(context))
+                    }
+                    ReportAction::Wait(duration) => SimulatedAction::Wait {
+                        ms: duration.as_millis() as u64,
+                    },
+                })
+                .collect();
+            DeviceSimulation {
+                connected: device.is_connected(),
+                device: device.name,
+                actions,
+            }
+        })
+        .collect()
+}
+
+// The reports the rule sends when the window gets focus, after `previous`.
+// Fullscreen rules only send when the fullscreen state changes.
+fn window_reports<'a>(
+    rule: &'a Rule,
+    window: &WindowMetadata,
+    previous: Option<&WindowMetadata>,
+) -> Option<&'a [Report]> {
+    match &rule.event {
+        Event::FocusedWindowChanged(_) => rule.window_reports(window),
+        Event::FullscreenChanged(event_cfg) => {
+            let is_fullscreen = window.is_fullscreen.unwrap_or(false);
+            let was_fullscreen = previous.map(|previous| previous.is_fullscreen.unwrap_or(false));
+            (was_fullscreen != Some(is_fullscreen)).then(|| event_cfg.reports(is_fullscreen))
+        }
+        _ => None,
+    }
+}
+
+pub fn reacts_to_windows(rule: &Rule) -> bool {
+    matches!(
+        rule.event,
+        Event::FocusedWindowChanged(_) | Event::FullscreenChanged(_)
+    )
+}
+
+// Dry runs the rule over windows focused one after another, e.g. from an
+// event log. Windows the rule sends nothing for are left out.
+pub fn simulate_windows(
+    config: &Config,
+    rule: &Rule,
+    windows: &[WindowMetadata],
+) -> Vec<WindowSimulation> {
+    let mut simulations = Vec::new();
+    let mut previous = None;
+    for window in windows {
+        if let Some(reports) = window_reports(rule, window, previous) {
+            let context = TemplateContext {
+                window: window.clone(),
+                ..TemplateContext::current()
+            };
+            simulations.push(WindowSimulation {
+                window: window.clone(),
+                devices: simulate_reports(config, rule, reports, &context),
+            });
+        }
+        previous = Some(window);
+    }
+    simulations
+}
+
+// The focused windows in an event log of JSON lines: the notifications
+// streamed over the control socket, or bare events as in an exported history.
+// Other events and responses are skipped.
+pub fn parse_window_log(log: &str) -> Result<Vec<WindowMetadata>> {
+    let mut windows = Vec::new();
+    for (i, line) in log.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut event: Value =
+            serde_json::from_str(line).with_context(|| format!("line {}", i + 1))?;
+        if event["method"] == "event" {
+            event = event["params"].take();
+        }
+        if event["type"] != "focused_window_changed" {
+            continue;
+        }
+        let window = serde_json::from_value(event["window"].take())
+            .with_context(|| format!("line {}", i + 1))?;
+        windows.push(window);
+    }
+    Ok(windows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titled(title: &str) -> WindowMetadata {
+        WindowMetadata {
+            title: Some(title.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_socket_and_history_lines() {
+        let log = r#"
+{"jsonrpc":"2.0","result":null,"id":1}
+{"jsonrpc":"2.0","method":"event","params":{"type":"focused_window_changed","window":{"title":"socket"}}}
+{"jsonrpc":"2.0","method":"event","params":{"type":"rule_sent","rule":"a","event":"interval","devices":[],"reports":1}}
+{"type":"focused_window_changed","window":{"title":"bare"}}
+
+{"time":"2024-01-02T13:45:06.000+01:00","type":"focused_window_changed","window":{"title":"history"}}
+"#;
+        assert_eq!(
+            parse_window_log(log).unwrap(),
+            [titled("socket"), titled("bare"), titled("history")]
+        );
+    }
+
+    #[test]
+    fn reports_the_broken_line() {
+        let log = "{\"type\":\"focused_window_changed\",\"window\":{}}\nnot json\n";
+        let error = parse_window_log(log).unwrap_err();
+        assert_eq!(error.to_string(), "line 2");
+    }
+}
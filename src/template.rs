@@ -0,0 +1,467 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    media::{self, MediaState, PlaybackStatus},
+    metrics::{self, Metrics},
+    webhook,
+    win::{self, WindowMetadata},
+};
+
+// Report bytes computed at send time. Literal bytes are written as hex, with
+// placeholders in braces, e.g. `01 {title:ascii:20} {time:hh}{time:mm} {crc8}`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Bytes(Vec<u8>),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextEncoding {
+    Utf8,
+    Ascii,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextSource {
+    WindowTitle,
+    MediaArtist,
+    MediaTitle,
+    MediaAlbum,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TimeField {
+    Hour,
+    Minute,
+    Second,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Placeholder {
+    // Text such as the focused window's title, truncated or zero padded to
+    // `length` bytes.
+    Text {
+        source: TextSource,
+        encoding: TextEncoding,
+        length: usize,
+    },
+    Time(TimeField),
+    // The media player's status as a single byte: 0 stopped, 1 paused,
+    // 2 playing.
+    MediaStatus,
+    // A field of the triggering webhook request's JSON body as text, like
+    // `Text`.
+    BodyText {
+        field: String,
+        encoding: TextEncoding,
+        length: usize,
+    },
+    // A field of the triggering webhook request's JSON body as a single byte:
+    // numbers rounded and clamped, true is 1, anything else 0.
+    BodyByte(String),
+    // A system metric scaled from one range onto another and clamped, as a
+    // single byte.
+    Metric {
+        name: String,
+        from: (f64, f64),
+        to: (f64, f64),
+    },
+    // CRC-8 over every byte before the placeholder.
+    Crc8,
+}
+
+// Values placeholders are rendered from, captured just before sending.
+pub struct TemplateContext {
+    pub window: WindowMetadata,
+    pub now: DateTime<Local>,
+    pub metrics: Metrics,
+    pub media: MediaState,
+    // The webhook request's JSON body, null for other events.
+    pub body: Value,
+}
+
+impl TemplateContext {
+    pub fn current() -> Self {
+        Self {
+            window: win::get_focused_window(),
+            now: Local::now(),
+            metrics: metrics::get_metrics(),
+            media: media::get_media(),
+            body: Value::Null,
+        }
+    }
+
+    pub fn with_body(body: Value) -> Self {
+        Self {
+            body,
+            ..Self::current()
+        }
+    }
+}
+
+fn parse_text_format(encoding: &str, length: &str) -> Result<(TextEncoding, usize)> {
+    let encoding = match encoding {
+        "utf8" => TextEncoding::Utf8,
+        "ascii" => TextEncoding::Ascii,
+        other => anyhow::bail!("unknown text encoding {other}, expected utf8 or ascii"),
+    };
+    let length = length
+        .parse()
+        .with_context(|| format!("invalid text length {length}"))?;
+    Ok((encoding, length))
+}
+
+fn parse_text(source: TextSource, encoding: &str, length: &str) -> Result<Placeholder> {
+    let (encoding, length) = parse_text_format(encoding, length)?;
+    Ok(Placeholder::Text {
+        source,
+        encoding,
+        length,
+    })
+}
+
+// Truncates or zero pads the text to `length` bytes.
+fn encode_text(text: &str, encoding: TextEncoding, length: usize) -> Vec<u8> {
+    let mut bytes: Vec<u8> = match encoding {
+        TextEncoding::Utf8 => {
+            // Don't cut a character in half.
+            let mut end = text.len().min(length);
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.as_bytes()[..end].to_vec()
+        }
+        TextEncoding::Ascii => text
+            .chars()
+            .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+            .take(length)
+            .collect(),
+    };
+    bytes.resize(length, 0);
+    bytes
+}
+
+fn parse_range(min: &str, max: &str) -> Result<(f64, f64)> {
+    let min = min
+        .parse()
+        .with_context(|| format!("invalid range start {min}"))?;
+    let max = max
+        .parse()
+        .with_context(|| format!("invalid range end {max}"))?;
+    Ok((min, max))
+}
+
+impl Placeholder {
+    fn parse(placeholder: &str) -> Result<Self> {
+        let parts: Vec<&str> = placeholder.split(':').map(str::trim).collect();
+        match parts.as_slice() {
+            ["title", encoding, length] => parse_text(TextSource::WindowTitle, encoding, length),
+            ["media", "status"] => Ok(Self::MediaStatus),
+            ["media", field, encoding, length] => {
+                let source = match *field {
+                    "artist" => TextSource::MediaArtist,
+                    "title" => TextSource::MediaTitle,
+                    "album" => TextSource::MediaAlbum,
+                    other => anyhow::bail!(
+                        "unknown media field {other}, expected status, artist, title or album"
+                    ),
+                };
+                parse_text(source, encoding, length)
+            }
+            ["body", field] if !field.is_empty() => Ok(Self::BodyByte(field.to_string())),
+            ["body", field, encoding, length] if !field.is_empty() => {
+                let (encoding, length) = parse_text_format(encoding, length)?;
+                Ok(Self::BodyText {
+                    field: field.to_string(),
+                    encoding,
+                    length,
+                })
+            }
+            ["time", field] => Ok(Self::Time(match *field {
+                "hh" => TimeField::Hour,
+                "mm" => TimeField::Minute,
+                "ss" => TimeField::Second,
+                other => anyhow::bail!("unknown time field {other}, expected hh, mm or ss"),
+            })),
+            ["crc8"] => Ok(Self::Crc8),
+            [name, range @ ..] if Metrics::default().get(name).is_some() => {
+                let (from, to) = match range {
+                    [] => ((0.0, 255.0), (0.0, 255.0)),
+                    [from_min, from_max] => (parse_range(from_min, from_max)?, (0.0, 255.0)),
+                    [from_min, from_max, to_min, to_max] => (
+                        parse_range(from_min, from_max)?,
+                        parse_range(to_min, to_max)?,
+                    ),
+                    _ => anyhow::bail!(
+                        "expected {{{name}}}, {{{name}:FROM_MIN:FROM_MAX}} or {{{name}:FROM_MIN:FROM_MAX:TO_MIN:TO_MAX}}"
+                    ),
+                };
+                Ok(Self::Metric {
+                    name: name.to_string(),
+                    from,
+                    to,
+                })
+            }
+            _ => anyhow::bail!("unknown placeholder {{{placeholder}}}"),
+        }
+    }
+
+    fn render(&self, context: &TemplateContext, rendered: &[u8]) -> Vec<u8> {
+        match *self {
+            Self::Text {
+                source,
+                encoding,
+                length,
+            } => {
+                let text = match source {
+                    TextSource::WindowTitle => context.window.title.as_deref(),
+                    TextSource::MediaArtist => context.media.artist.as_deref(),
+                    TextSource::MediaTitle => context.media.title.as_deref(),
+                    TextSource::MediaAlbum => context.media.album.as_deref(),
+                }
+                .unwrap_or_default();
+                encode_text(text, encoding, length)
+            }
+            Self::Time(field) => vec![match field {
+                TimeField::Hour => context.now.hour() as u8,
+                TimeField::Minute => context.now.minute() as u8,
+                TimeField::Second => context.now.second() as u8,
+            }],
+            Self::MediaStatus => vec![match context.media.status {
+                PlaybackStatus::Stopped => 0,
+                PlaybackStatus::Paused => 1,
+                PlaybackStatus::Playing => 2,
+            }],
+            Self::BodyText {
+                ref field,
+                encoding,
+                length,
+            } => {
+                let text = match webhook::body_field(&context.body, field) {
+                    Some(Value::String(text)) => text.clone(),
+                    Some(Value::Null) | None => String::new(),
+                    Some(value) => value.to_string(),
+                };
+                encode_text(&text, encoding, length)
+            }
+            Self::BodyByte(ref field) => vec![match webhook::body_field(&context.body, field) {
+                Some(Value::Number(number)) => number
+                    .as_f64()
+                    .unwrap_or_default()
+                    .round()
+                    .clamp(0.0, 255.0) as u8,
+                Some(Value::Bool(value)) => u8::from(*value),
+                _ => 0,
+            }],
+            Self::Metric {
+                ref name,
+                from: (from_min, from_max),
+                to: (to_min, to_max),
+            } => {
+                let value = context.metrics.get(name).unwrap_or_default();
+                let scaled = if from_max == from_min {
+                    to_min
+                } else {
+                    to_min + (value - from_min) * (to_max - to_min) / (from_max - from_min)
+                };
+                let clamped = scaled
+                    .clamp(to_min.min(to_max), to_min.max(to_max))
+                    .clamp(0.0, 255.0);
+                vec![clamped.round() as u8]
+            }
+            Self::Crc8 => vec![crc8(rendered)],
+        }
+    }
+}
+
+// CRC-8 with polynomial 0x07 (CRC-8/SMBUS).
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+impl Template {
+    pub fn render(&self, context: &TemplateContext) -> Vec<u8> {
+        let mut rendered = Vec::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Bytes(bytes) => rendered.extend_from_slice(bytes),
+                Segment::Placeholder(placeholder) => {
+                    let bytes = placeholder.render(context, &rendered);
+                    rendered.extend(bytes);
+                }
+            }
+        }
+        rendered
+    }
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        while !rest.is_empty() {
+            let (literal, after) = match rest.find('{') {
+                Some(start) => (&rest[..start], Some(&rest[start + 1..])),
+                None => (rest, None),
+            };
+
+            let hex_digits: String = literal.split_whitespace().collect();
+            if !hex_digits.is_empty() {
+                let bytes = hex::decode(&hex_digits)
+                    .with_context(|| format!("invalid hex bytes {}", literal.trim()))?;
+                segments.push(Segment::Bytes(bytes));
+            }
+
+            let Some(after) = after else {
+                break;
+            };
+            let end = after.find('}').context("unclosed placeholder, missing }")?;
+            segments.push(Segment::Placeholder(Placeholder::parse(&after[..end])?));
+            rest = &after[end + 1..];
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+}
+
+impl TryFrom<String> for Template {
+    type Error = anyhow::Error;
+
+    fn try_from(source: String) -> Result<Self> {
+        source.parse()
+    }
+}
+
+impl From<Template> for String {
+    fn from(template: Template) -> Self {
+        template.source
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            window: WindowMetadata {
+                title: Some("Héllo".to_string()),
+                ..Default::default()
+            },
+            now: Local.with_ymd_and_hms(2024, 1, 2, 13, 45, 6).unwrap(),
+            metrics: Metrics {
+                cpu: 50.0,
+                ..Default::default()
+            },
+            media: MediaState {
+                status: PlaybackStatus::Paused,
+                artist: Some("A".to_string()),
+                ..Default::default()
+            },
+            body: json!({ "status": "ok", "build": { "duration": 300, "success": true } }),
+        }
+    }
+
+    fn render(source: &str) -> Vec<u8> {
+        source.parse::<Template>().unwrap().render(&context())
+    }
+
+    #[test]
+    fn renders_text() {
+        assert_eq!(render("{title:ascii:8}"), b"H?llo\0\0\0");
+        // é is two bytes, it is dropped rather than cut in half.
+        assert_eq!(render("{title:utf8:2}"), b"H\0");
+        assert_eq!(render("{media:status} {media:artist:ascii:2}"), b"\x01A\0");
+    }
+
+    #[test]
+    fn renders_time() {
+        assert_eq!(render("01 {time:hh}{time:mm} {time:ss}"), [1, 13, 45, 6]);
+    }
+
+    #[test]
+    fn renders_body_fields() {
+        assert_eq!(render("{body:status:ascii:3}"), b"ok\0");
+        assert_eq!(render("{body:/build/duration:utf8:4}"), b"300\0");
+        assert_eq!(render("{body:/build/duration}"), [255]);
+        assert_eq!(render("{body:/build/success}"), [1]);
+        assert_eq!(render("{body:missing} {body:missing:ascii:1}"), [0, 0]);
+    }
+
+    #[test]
+    fn scales_metrics() {
+        assert_eq!(render("{cpu}"), [50]);
+        assert_eq!(render("{cpu:0:100}"), [128]);
+        assert_eq!(render("{cpu:0:100:10:0}"), [5]);
+        assert_eq!(render("{cpu:0:10}"), [255]);
+    }
+
+    #[test]
+    fn appends_crc8() {
+        assert_eq!(crc8(b"123456789"), 0xf4);
+        assert_eq!(
+            render("31 32 33 34 35 36 37 38 39 {crc8}"),
+            b"123456789\xf4"
+        );
+    }
+
+    #[test]
+    fn keeps_the_source() {
+        let source = "01 {title:ascii:8} {crc8}";
+        let template: Template = source.parse().unwrap();
+        assert_eq!(template.to_string(), source);
+        assert_eq!(String::from(template), source);
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        for source in [
+            "0g",
+            "{title:ascii:8",
+            "{nope}",
+            "{title:latin1:4}",
+            "{title:ascii:x}",
+            "{time:yy}",
+            "{cpu:1}",
+            "{body:}",
+        ] {
+            assert!(source.parse::<Template>().is_err(), "{source}");
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::Read;
+use tiny_http::{Method, Request, Response, Server};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{config, engine};
+
+// Bodies only carry a few fields, anything larger is refused.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+// A top-level field such as `status`, or a JSON pointer such as `/build/status`.
+pub fn body_field<'a>(body: &'a Value, field: &str) -> Option<&'a Value> {
+    if field.starts_with('/') {
+        body.pointer(field)
+    } else {
+        body.get(field)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WebhookOutcome {
+    Fired,
+    Unauthorized,
+    // No enabled rule listens on the name.
+    NotFound,
+}
+
+// A request checked and answered by `handle` inside the engine, against the
+// current config so token and rule edits apply right away.
+pub struct WebhookCall {
+    name: String,
+    token: Option<String>,
+    body: Value,
+    reply: oneshot::Sender<WebhookOutcome>,
+}
+
+pub fn handle(call: WebhookCall) {
+    let config = engine::config();
+    let authorized = config
+        .webhook
+        .as_ref()
+        .is_some_and(|listener| listener.is_authorized(call.token.as_deref()));
+
+    let outcome = if authorized {
+        let rules: Vec<(&config::Rule, &config::WebhookConfig)> = config
+            .rules
+            .iter()
+            .filter(|rule| !rule.disabled)
+            .filter_map(|rule| match &rule.event {
+                config::Event::Webhook(event_cfg) if event_cfg.name == call.name => {
+                    Some((rule, event_cfg))
+                }
+                _ => None,
+            })
+            .collect();
+
+        if rules.is_empty() {
+            WebhookOutcome::NotFound
+        } else {
+            // The body goes along with the reports, `{body:...}` placeholders
+            // render the request that triggered them.
+            for (rule, event_cfg) in rules {
+                rule.send_with_body(&config, &event_cfg.reports, &call.body);
+            }
+            WebhookOutcome::Fired
+        }
+    } else {
+        WebhookOutcome::Unauthorized
+    };
+
+    let _ = call.reply.send(outcome);
+}
+
+fn bearer_token(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+fn read_body(request: &mut Request) -> Result<Value, u16> {
+    if request
+        .body_length()
+        .is_some_and(|length| length > MAX_BODY_BYTES)
+    {
+        return Err(413);
+    }
+
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|_| 400u16)?;
+    if body.len() > MAX_BODY_BYTES {
+        return Err(413);
+    }
+
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Value::Null);
+    }
+    serde_json::from_slice(&body).map_err(|_| 400)
+}
+
+fn respond(mut request: Request, calls: &mpsc::UnboundedSender<WebhookCall>) {
+    let path = request.url().split('?').next().unwrap_or_default();
+    let name = path
+        .strip_prefix("/webhook/")
+        .unwrap_or_default()
+        .to_string();
+
+    let status = if name.is_empty() {
+        404
+    } else if *request.method() != Method::Post {
+        405
+    } else {
+        match read_body(&mut request) {
+            Err(status) => status,
+            Ok(body) => {
+                let (reply, reply_rx) = oneshot::channel();
+                let call = WebhookCall {
+                    name,
+                    token: bearer_token(&request),
+                    body,
+                    reply,
+                };
+                if calls.send(call).is_err() {
+                    503
+                } else {
+                    match reply_rx.blocking_recv() {
+                        Ok(WebhookOutcome::Fired) => 204,
+                        Ok(WebhookOutcome::Unauthorized) => 401,
+                        Ok(WebhookOutcome::NotFound) => 404,
+                        Err(_) => 503,
+                    }
+                }
+            }
+        }
+    };
+
+    if let Err(e) = request.respond(Response::empty(status)) {
+        tracing::error!("webhook: failed to respond: {}", e);
+    }
+}
+
+// Listens on localhost, requests come out of the returned channel and must be
+// passed to `handle`.
+pub fn start_server(
+    listener: &config::WebhookListenerConfig,
+) -> Result<mpsc::UnboundedReceiver<WebhookCall>> {
+    anyhow::ensure!(
+        !listener.token.is_empty(),
+        "the webhook listener needs a token"
+    );
+
+    let server = Server::http(("127.0.0.1", listener.port))
+        .map_err(|e| anyhow::anyhow!("failed to listen on 127.0.0.1:{}: {}", listener.port, e))?;
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    std::thread::Builder::new()
+        .name("webhook-listener".into())
+        .spawn(move || {
+            for request in server.incoming_requests() {
+                respond(request, &tx);
+            }
+        })
+        .context("failed to spawn webhook listener thread")?;
+    Ok(rx)
+}
@@ -1,47 +1,68 @@
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::{
-    ffi::OsString,
-    os::windows::ffi::OsStringExt,
     path::PathBuf,
-    sync::{LazyLock, Mutex},
-};
-use tokio::sync::watch;
-use windows::{
-    Win32::{
-        Foundation::{CloseHandle, HANDLE, HWND},
-        System::Threading::{
-            OpenProcess, PROCESS_NAME_FORMAT, PROCESS_QUERY_LIMITED_INFORMATION,
-            QueryFullProcessImageNameW,
-        },
-        UI::{
-            Accessibility::{HWINEVENTHOOK, SetWinEventHook, UnhookWinEvent},
-            WindowsAndMessaging::{
-                EVENT_SYSTEM_FOREGROUND, GetClassNameW, GetWindowTextLengthW, GetWindowTextW,
-                GetWindowThreadProcessId, OBJID_WINDOW, WINEVENT_OUTOFCONTEXT,
-            },
-        },
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicBool, Ordering},
     },
-    core::PWSTR,
+    time::Duration,
 };
+use tokio::sync::watch;
+
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+#[cfg(windows)]
+pub use win32::{pump_messages, query_focused_window, start_foreground_hook};
+#[cfg(target_os = "linux")]
+pub use x11::{query_focused_window, start_foreground_hook};
 
 pub static FOCUSED_WINDOW: LazyLock<Mutex<WindowMetadata>> =
     LazyLock::new(|| Mutex::new(WindowMetadata::default()));
 
+// Title changes come in bursts, e.g. a page loading, so they are pushed at
+// most once per interval with the latest title.
+const TITLE_THROTTLE: Duration = Duration::from_millis(250);
+
+static TITLE_PUSH_PENDING: AtomicBool = AtomicBool::new(false);
+
 pub static FOCUSED_WINDOW_TX: LazyLock<watch::Sender<WindowMetadata>> = LazyLock::new(|| {
     let (tx, _rx) = watch::channel(WindowMetadata::default());
     tx
 });
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct WindowMetadata {
     pub title: Option<String>,
     pub class: Option<String>,
     pub exe: Option<PathBuf>,
+    // Whether the window covers its whole monitor, e.g. a game or video.
+    pub is_fullscreen: Option<bool>,
+    // The active workspace's name, or its index when it has none.
+    pub workspace: Option<String>,
 }
 
 impl WindowMetadata {
+    // Whether the window matches `window`, a rule's pattern: any one of its
+    // title, class and exe, and its workspace and fullscreen state when set.
+    // A pattern with only the latter matches any window in that state.
     pub fn match_any(&self, window: &WindowMetadata) -> bool {
+        if window.workspace.is_some() && self.workspace != window.workspace {
+            return false;
+        }
+
+        if let Some(fullscreen_other) = window.is_fullscreen
+            && self.is_fullscreen.unwrap_or(false) != fullscreen_other
+        {
+            return false;
+        }
+
+        if window.title.is_none() && window.class.is_none() && window.exe.is_none() {
+            return window.workspace.is_some() || window.is_fullscreen.is_some();
+        }
+
         if let Some(title_self) = &self.title
             && let Some(title_other) = &window.title
             && title_self.contains(title_other)
@@ -67,88 +88,6 @@ impl WindowMetadata {
     }
 }
 
-pub struct WinHook {
-    hook: HWINEVENTHOOK,
-}
-impl Drop for WinHook {
-    fn drop(&mut self) {
-        unsafe {
-            let _ = UnhookWinEvent(self.hook);
-        }
-    }
-}
-
-struct HandleGuard(HANDLE);
-impl Drop for HandleGuard {
-    fn drop(&mut self) {
-        unsafe {
-            let _ = CloseHandle(self.0);
-        }
-    }
-}
-
-#[inline]
-fn hwnd_title(hwnd: HWND) -> Option<String> {
-    unsafe {
-        let len = GetWindowTextLengthW(hwnd);
-        let mut buf = vec![0u16; (len + 1) as usize];
-        let n = GetWindowTextW(hwnd, &mut buf);
-        if n > 0 {
-            buf.truncate(n as usize);
-            Some(OsString::from_wide(&buf).to_string_lossy().into_owned())
-        } else {
-            None
-        }
-    }
-}
-
-#[inline]
-fn hwnd_class(hwnd: HWND) -> Option<String> {
-    unsafe {
-        let mut buf = vec![0u16; 256];
-        let n = GetClassNameW(hwnd, &mut buf);
-        if n > 0 {
-            buf.truncate(n as usize);
-            Some(OsString::from_wide(&buf).to_string_lossy().into_owned())
-        } else {
-            None
-        }
-    }
-}
-
-#[inline]
-fn hwnd_pid(hwnd: HWND) -> Option<u32> {
-    let mut pid = 0u32;
-    unsafe {
-        let _tid = GetWindowThreadProcessId(hwnd, Some(&mut pid));
-    }
-    if pid == 0 { None } else { Some(pid) }
-}
-
-#[inline]
-fn process_exe(pid: u32) -> Option<PathBuf> {
-    unsafe {
-        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
-        if handle.is_invalid() {
-            return None;
-        }
-        let _guard = HandleGuard(handle);
-
-        let mut buf = vec![0u16; 1024];
-        let mut size = buf.len() as u32;
-        QueryFullProcessImageNameW(
-            handle,
-            PROCESS_NAME_FORMAT(0),
-            PWSTR(buf.as_mut_ptr()),
-            &mut size,
-        )
-        .ok()?;
-
-        buf.truncate(size as usize);
-        Some(OsString::from_wide(&buf).into())
-    }
-}
-
 pub fn get_focused_window() -> WindowMetadata {
     match FOCUSED_WINDOW.lock() {
         Ok(guard) => guard.clone(),
@@ -165,64 +104,125 @@ pub fn set_focused_window(window: WindowMetadata) {
             let _ = FOCUSED_WINDOW_TX.send(window);
         }
         Err(e) => {
-            eprintln!("update_focused_window: failed to acquire lock: {}", e);
+            tracing::error!("update_focused_window: failed to acquire lock: {}", e);
         }
     }
 }
 
-pub fn start_foreground_hook() -> Result<WinHook> {
-    let flags = WINEVENT_OUTOFCONTEXT;
-
-    let hook = unsafe {
-        SetWinEventHook(
-            EVENT_SYSTEM_FOREGROUND,
-            EVENT_SYSTEM_FOREGROUND,
-            None,
-            Some(win_event_proc),
-            0,
-            0,
-            flags,
-        )
+// Workspaces are only detected on Linux, see `workspace`.
+#[cfg(target_os = "linux")]
+pub fn set_focused_window_workspace(workspace: Option<String>) {
+    let window = match FOCUSED_WINDOW.lock() {
+        Ok(mut guard) => {
+            guard.workspace = workspace;
+            guard.clone()
+        }
+        Err(e) => {
+            tracing::error!(
+                "set_focused_window_workspace: failed to acquire lock: {}",
+                e
+            );
+            return;
+        }
     };
+    let _ = FOCUSED_WINDOW_TX.send(window);
+}
 
-    if hook.is_invalid() {
-        anyhow::bail!("SetWinEventHook failed");
+// Updates the title of the focused window, e.g. after a browser tab switch.
+pub fn set_focused_window_title(title: Option<String>) {
+    match FOCUSED_WINDOW.lock() {
+        Ok(mut guard) => {
+            if guard.title == title {
+                return;
+            }
+            guard.title = title;
+        }
+        Err(e) => {
+            tracing::error!("set_focused_window_title: failed to acquire lock: {}", e);
+            return;
+        }
     }
 
-    Ok(WinHook { hook })
-}
-
-unsafe extern "system" fn win_event_proc(
-    _hwineventhook: HWINEVENTHOOK,
-    event: u32,
-    hwnd: HWND,
-    id_object: i32,
-    _idchild: i32,
-    _ideventthread: u32,
-    _dwmseventtime: u32,
-) {
-    if event != EVENT_SYSTEM_FOREGROUND {
-        println!("win_event_proc: event != EVENT_SYSTEM_FOREGROUND");
+    if TITLE_PUSH_PENDING.swap(true, Ordering::AcqRel) {
         return;
     }
 
-    if id_object != OBJID_WINDOW.0 {
-        println!("win_event_proc: id_object != OBJID_WINDOW");
-        return;
+    let spawned = std::thread::Builder::new()
+        .name("title-throttle".into())
+        .spawn(|| {
+            std::thread::sleep(TITLE_THROTTLE);
+            TITLE_PUSH_PENDING.store(false, Ordering::Release);
+
+            let window = get_focused_window();
+            FOCUSED_WINDOW_TX.send_if_modified(|current| {
+                if *current == window {
+                    false
+                } else {
+                    *current = window;
+                    true
+                }
+            });
+        });
+
+    if let Err(e) = spawned {
+        TITLE_PUSH_PENDING.store(false, Ordering::Release);
+        tracing::error!("set_focused_window_title: failed to spawn thread: {}", e);
     }
+}
 
-    let window = WindowMetadata {
-        title: hwnd_title(hwnd),
-        class: hwnd_class(hwnd),
-        exe: hwnd_pid(hwnd).and_then(process_exe),
-    };
+#[cfg(test)]
+mod tests {
+    use super::WindowMetadata;
 
-    // Ignore alt + tab 'window'
-    if let Some(class) = &window.class
-        && class == "XamlExplorerHostIslandWindow"
-    {
-        return;
+    fn window(class: &str, workspace: &str, is_fullscreen: bool) -> WindowMetadata {
+        WindowMetadata {
+            class: Some(class.to_string()),
+            workspace: Some(workspace.to_string()),
+            is_fullscreen: Some(is_fullscreen),
+            ..Default::default()
+        }
     }
 
-    set_focused_window(window);
+    #[test]
+    fn workspace_and_fullscreen_narrow_the_match() {
+        let firefox = window("firefox", "2", false);
+        let terminal = window("terminal", "2", false);
+        let game = window("game", "1", true);
+
+        let firefox_on_2 = WindowMetadata {
+            class: Some("firefox".to_string()),
+            workspace: Some("2".to_string()),
+            ..Default::default()
+        };
+        assert!(firefox.match_any(&firefox_on_2));
+        assert!(!terminal.match_any(&firefox_on_2));
+        assert!(!window("firefox", "1", false).match_any(&firefox_on_2));
+
+        let fullscreen_game = WindowMetadata {
+            class: Some("game".to_string()),
+            is_fullscreen: Some(true),
+            ..Default::default()
+        };
+        assert!(game.match_any(&fullscreen_game));
+        assert!(!window("video", "1", true).match_any(&fullscreen_game));
+    }
+
+    #[test]
+    fn workspace_or_fullscreen_alone_match_any_window() {
+        let on_2 = WindowMetadata {
+            workspace: Some("2".to_string()),
+            ..Default::default()
+        };
+        assert!(window("firefox", "2", false).match_any(&on_2));
+        assert!(!window("firefox", "1", false).match_any(&on_2));
+
+        let fullscreen = WindowMetadata {
+            is_fullscreen: Some(true),
+            ..Default::default()
+        };
+        assert!(window("game", "1", true).match_any(&fullscreen));
+        assert!(!window("game", "1", false).match_any(&fullscreen));
+
+        assert!(!window("game", "1", true).match_any(&WindowMetadata::default()));
+    }
 }
@@ -0,0 +1,253 @@
+use anyhow::Result;
+use std::{ffi::OsString, os::windows::ffi::OsStringExt, path::PathBuf};
+use windows::{
+    Win32::{
+        Foundation::{CloseHandle, HANDLE, HWND, RECT},
+        Graphics::Gdi::{
+            GetMonitorInfoW, MONITOR_DEFAULTTONEAREST, MONITORINFO, MonitorFromWindow,
+        },
+        System::Threading::{
+            OpenProcess, PROCESS_NAME_FORMAT, PROCESS_QUERY_LIMITED_INFORMATION,
+            QueryFullProcessImageNameW,
+        },
+        UI::{
+            Accessibility::{HWINEVENTHOOK, SetWinEventHook, UnhookWinEvent},
+            WindowsAndMessaging::{
+                CHILDID_SELF, DispatchMessageW, EVENT_OBJECT_LOCATIONCHANGE,
+                EVENT_OBJECT_NAMECHANGE, EVENT_SYSTEM_FOREGROUND, GetClassNameW,
+                GetForegroundWindow, GetMessageW, GetWindowRect, GetWindowTextLengthW,
+                GetWindowTextW, GetWindowThreadProcessId, MSG, OBJID_WINDOW, TranslateMessage,
+                WINEVENT_OUTOFCONTEXT,
+            },
+        },
+    },
+    core::PWSTR,
+};
+
+use crate::workspace;
+
+use super::{WindowMetadata, get_focused_window, set_focused_window, set_focused_window_title};
+
+pub struct WinHook {
+    hooks: Vec<HWINEVENTHOOK>,
+}
+impl Drop for WinHook {
+    fn drop(&mut self) {
+        for hook in self.hooks.iter() {
+            unsafe {
+                let _ = UnhookWinEvent(*hook);
+            }
+        }
+    }
+}
+
+struct HandleGuard(HANDLE);
+impl Drop for HandleGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+#[inline]
+fn hwnd_title(hwnd: HWND) -> Option<String> {
+    unsafe {
+        let len = GetWindowTextLengthW(hwnd);
+        let mut buf = vec![0u16; (len + 1) as usize];
+        let n = GetWindowTextW(hwnd, &mut buf);
+        if n > 0 {
+            buf.truncate(n as usize);
+            Some(OsString::from_wide(&buf).to_string_lossy().into_owned())
+        } else {
+            None
+        }
+    }
+}
+
+#[inline]
+fn hwnd_class(hwnd: HWND) -> Option<String> {
+    unsafe {
+        let mut buf = vec![0u16; 256];
+        let n = GetClassNameW(hwnd, &mut buf);
+        if n > 0 {
+            buf.truncate(n as usize);
+            Some(OsString::from_wide(&buf).to_string_lossy().into_owned())
+        } else {
+            None
+        }
+    }
+}
+
+#[inline]
+fn hwnd_pid(hwnd: HWND) -> Option<u32> {
+    let mut pid = 0u32;
+    unsafe {
+        let _tid = GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    }
+    if pid == 0 { None } else { Some(pid) }
+}
+
+#[inline]
+fn process_exe(pid: u32) -> Option<PathBuf> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        if handle.is_invalid() {
+            return None;
+        }
+        let _guard = HandleGuard(handle);
+
+        let mut buf = vec![0u16; 1024];
+        let mut size = buf.len() as u32;
+        QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_FORMAT(0),
+            PWSTR(buf.as_mut_ptr()),
+            &mut size,
+        )
+        .ok()?;
+
+        buf.truncate(size as usize);
+        Some(OsString::from_wide(&buf).into())
+    }
+}
+
+// The desktop covers the whole monitor too, but isn't a fullscreen app.
+const SHELL_CLASSES: [&str; 2] = ["Progman", "WorkerW"];
+
+#[inline]
+fn hwnd_is_fullscreen(hwnd: HWND, class: Option<&str>) -> bool {
+    if class.is_some_and(|class| SHELL_CLASSES.contains(&class)) {
+        return false;
+    }
+
+    unsafe {
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_err() {
+            return false;
+        }
+
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return false;
+        }
+
+        let monitor_rect = info.rcMonitor;
+        rect.left <= monitor_rect.left
+            && rect.top <= monitor_rect.top
+            && rect.right >= monitor_rect.right
+            && rect.bottom >= monitor_rect.bottom
+    }
+}
+
+fn window_metadata(hwnd: HWND) -> WindowMetadata {
+    let class = hwnd_class(hwnd);
+
+    WindowMetadata {
+        title: hwnd_title(hwnd),
+        exe: hwnd_pid(hwnd).and_then(process_exe),
+        is_fullscreen: Some(hwnd_is_fullscreen(hwnd, class.as_deref())),
+        workspace: workspace::get_workspace(),
+        class,
+    }
+}
+
+// The focused window right now, without a hook, e.g. for the command line.
+pub fn query_focused_window() -> Result<WindowMetadata> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_invalid() {
+        anyhow::bail!("no window is focused");
+    }
+    Ok(window_metadata(hwnd))
+}
+
+pub fn start_foreground_hook() -> Result<WinHook> {
+    let flags = WINEVENT_OUTOFCONTEXT;
+
+    let mut hooks = WinHook { hooks: Vec::new() };
+    // Location changes catch the focused window entering or leaving
+    // fullscreen, e.g. F11 in a browser. Name changes catch title only
+    // changes, e.g. switching browser tabs.
+    for event in [
+        EVENT_SYSTEM_FOREGROUND,
+        EVENT_OBJECT_LOCATIONCHANGE,
+        EVENT_OBJECT_NAMECHANGE,
+    ] {
+        let hook =
+            unsafe { SetWinEventHook(event, event, None, Some(win_event_proc), 0, 0, flags) };
+
+        if hook.is_invalid() {
+            anyhow::bail!("SetWinEventHook failed");
+        }
+        hooks.hooks.push(hook);
+    }
+
+    Ok(hooks)
+}
+
+// Out of context hooks are delivered through the installing thread's message
+// queue, so without a UI event loop that thread has to pump it. Runs until the
+// queue is closed.
+pub fn pump_messages() {
+    let mut msg = MSG::default();
+    unsafe {
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+unsafe extern "system" fn win_event_proc(
+    _hwineventhook: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    id_child: i32,
+    _ideventthread: u32,
+    _dwmseventtime: u32,
+) {
+    if id_object != OBJID_WINDOW.0 {
+        return;
+    }
+
+    match event {
+        EVENT_SYSTEM_FOREGROUND => {
+            let window = window_metadata(hwnd);
+
+            // Ignore alt + tab 'window'
+            if let Some(class) = &window.class
+                && class == "XamlExplorerHostIslandWindow"
+            {
+                return;
+            }
+
+            set_focused_window(window);
+            crate::layout::refresh_layout();
+        }
+        EVENT_OBJECT_LOCATIONCHANGE => {
+            if hwnd != unsafe { GetForegroundWindow() } {
+                return;
+            }
+
+            let mut window = get_focused_window();
+            let is_fullscreen = Some(hwnd_is_fullscreen(hwnd, window.class.as_deref()));
+            if window.is_fullscreen != is_fullscreen {
+                window.is_fullscreen = is_fullscreen;
+                set_focused_window(window);
+            }
+        }
+        EVENT_OBJECT_NAMECHANGE => {
+            if id_child != CHILDID_SELF as i32 || hwnd != unsafe { GetForegroundWindow() } {
+                return;
+            }
+
+            set_focused_window_title(hwnd_title(hwnd));
+        }
+        _ => {}
+    }
+}
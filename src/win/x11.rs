@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        Event,
+        xproto::{AtomEnum, ChangeWindowAttributesAux, ConnectionExt, EventMask, Window},
+    },
+    rust_connection::RustConnection,
+};
+
+use crate::workspace;
+
+use super::{WindowMetadata, get_focused_window, set_focused_window, set_focused_window_title};
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        _NET_WM_STATE,
+        _NET_WM_STATE_FULLSCREEN,
+        UTF8_STRING,
+    }
+}
+
+// The watcher thread lives as long as the process, there is nothing to undo.
+pub struct WinHook;
+
+struct ActiveWindowWatcher {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+    active: Option<Window>,
+}
+
+// The focused window right now, without a watcher thread, e.g. for the
+// command line.
+pub fn query_focused_window() -> Result<WindowMetadata> {
+    let (conn, screen) = x11rb::connect(None).context("failed to connect to X11")?;
+    let root = conn.setup().roots[screen].root;
+    let atoms = Atoms::new(&conn)?.reply()?;
+
+    let watcher = ActiveWindowWatcher {
+        conn,
+        root,
+        atoms,
+        active: None,
+    };
+    let window = watcher.active_window().context("no window is focused")?;
+    Ok(watcher.metadata(window))
+}
+
+pub fn start_foreground_hook() -> Result<WinHook> {
+    let (conn, screen) = x11rb::connect(None).context("failed to connect to X11")?;
+    let root = conn.setup().roots[screen].root;
+    let atoms = Atoms::new(&conn)?.reply()?;
+
+    conn.change_window_attributes(
+        root,
+        &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+    )?;
+    conn.flush()?;
+
+    let mut watcher = ActiveWindowWatcher {
+        conn,
+        root,
+        atoms,
+        active: None,
+    };
+
+    std::thread::Builder::new()
+        .name("foreground-hook".into())
+        .spawn(move || watcher.run())
+        .context("failed to spawn foreground hook thread")?;
+
+    Ok(WinHook)
+}
+
+impl ActiveWindowWatcher {
+    fn run(&mut self) {
+        self.active_window_changed();
+
+        loop {
+            let event = match self.conn.wait_for_event() {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::error!("foreground hook: X11 connection lost: {}", e);
+                    return;
+                }
+            };
+
+            let Event::PropertyNotify(event) = event else {
+                continue;
+            };
+
+            if event.window == self.root {
+                if event.atom == self.atoms._NET_ACTIVE_WINDOW {
+                    self.active_window_changed();
+                }
+                continue;
+            }
+
+            if Some(event.window) != self.active {
+                continue;
+            }
+
+            if event.atom == self.atoms._NET_WM_NAME || event.atom == u32::from(AtomEnum::WM_NAME) {
+                set_focused_window_title(self.title(event.window));
+            } else if event.atom == self.atoms._NET_WM_STATE {
+                let mut window = get_focused_window();
+                let is_fullscreen = Some(self.is_fullscreen(event.window));
+                if window.is_fullscreen != is_fullscreen {
+                    window.is_fullscreen = is_fullscreen;
+                    set_focused_window(window);
+                }
+            }
+        }
+    }
+
+    fn active_window_changed(&mut self) {
+        let active = self.active_window();
+        if active == self.active {
+            return;
+        }
+
+        // Property changes of the focused window itself, e.g. its title or
+        // fullscreen state.
+        if let Some(previous) = self.active {
+            let _ = self.conn.change_window_attributes(
+                previous,
+                &ChangeWindowAttributesAux::new().event_mask(EventMask::NO_EVENT),
+            );
+        }
+        if let Some(window) = active {
+            let _ = self.conn.change_window_attributes(
+                window,
+                &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+            );
+        }
+        let _ = self.conn.flush();
+        self.active = active;
+
+        let Some(window) = active else {
+            return;
+        };
+
+        set_focused_window(self.metadata(window));
+    }
+
+    fn metadata(&self, window: Window) -> WindowMetadata {
+        WindowMetadata {
+            title: self.title(window),
+            class: self.class(window),
+            exe: self.exe(window),
+            is_fullscreen: Some(self.is_fullscreen(window)),
+            workspace: workspace::get_workspace(),
+        }
+    }
+
+    fn property(&self, window: Window, property: u32, type_: u32) -> Option<Vec<u8>> {
+        self.conn
+            .get_property(false, window, property, type_, 0, u32::MAX / 4)
+            .ok()?
+            .reply()
+            .ok()
+            .map(|reply| reply.value)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn property32(&self, window: Window, property: u32, type_: AtomEnum) -> Vec<u32> {
+        self.conn
+            .get_property(false, window, property, type_, 0, u32::MAX / 4)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(Iterator::collect))
+            .unwrap_or_default()
+    }
+
+    fn active_window(&self) -> Option<Window> {
+        self.property32(self.root, self.atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW)
+            .first()
+            .copied()
+            .filter(|&window| window != x11rb::NONE)
+    }
+
+    fn title(&self, window: Window) -> Option<String> {
+        self.property(window, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)
+            .or_else(|| self.property(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()))
+            .map(|title| String::from_utf8_lossy(&title).into_owned())
+    }
+
+    // WM_CLASS holds the instance name then the class name, NUL separated.
+    fn class(&self, window: Window) -> Option<String> {
+        let class = self.property(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())?;
+        class
+            .split(|&b| b == 0)
+            .filter(|part| !part.is_empty())
+            .nth(1)
+            .map(|class| String::from_utf8_lossy(class).into_owned())
+    }
+
+    fn exe(&self, window: Window) -> Option<PathBuf> {
+        let pid = *self
+            .property32(window, self.atoms._NET_WM_PID, AtomEnum::CARDINAL)
+            .first()?;
+        std::fs::read_link(format!("/proc/{}/exe", pid)).ok()
+    }
+
+    fn is_fullscreen(&self, window: Window) -> bool {
+        self.property32(window, self.atoms._NET_WM_STATE, AtomEnum::ATOM)
+            .contains(&self.atoms._NET_WM_STATE_FULLSCREEN)
+    }
+}
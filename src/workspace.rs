@@ -0,0 +1,227 @@
+use std::sync::LazyLock;
+use tokio::sync::watch;
+
+#[cfg(target_os = "linux")]
+use crate::win;
+
+// The active workspace: its name, or its index when it has none. None until
+// first detected, or on platforms without workspace support.
+pub static WORKSPACE_TX: LazyLock<watch::Sender<Option<String>>> = LazyLock::new(|| {
+    let (tx, _rx) = watch::channel(None);
+    tx
+});
+
+pub fn get_workspace() -> Option<String> {
+    WORKSPACE_TX.borrow().clone()
+}
+
+#[cfg(target_os = "linux")]
+fn set_workspace(workspace: String) {
+    let changed = WORKSPACE_TX.send_if_modified(|current| {
+        if current.as_ref() == Some(&workspace) {
+            false
+        } else {
+            *current = Some(workspace.clone());
+            true
+        }
+    });
+
+    if changed {
+        win::set_focused_window_workspace(Some(workspace));
+    }
+}
+
+// Watches sway when SWAYSOCK is set, X11 otherwise.
+pub fn start_workspace_watcher() {
+    let spawned = std::thread::Builder::new()
+        .name("workspace-watcher".into())
+        .spawn(|| {
+            if let Err(e) = watch_workspaces() {
+                tracing::error!("start_workspace_watcher: {}", e);
+            }
+        });
+
+    if let Err(e) = spawned {
+        tracing::error!("start_workspace_watcher: failed to spawn thread: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn watch_workspaces() -> anyhow::Result<()> {
+    match std::env::var_os("SWAYSOCK") {
+        Some(socket) => sway::watch(socket.as_ref()),
+        None => x11::watch(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn watch_workspaces() -> anyhow::Result<()> {
+    anyhow::bail!("workspace detection is not supported on this platform")
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use anyhow::{Context, Result};
+    use x11rb::{
+        connection::Connection,
+        protocol::{
+            Event,
+            xproto::{AtomEnum, ChangeWindowAttributesAux, ConnectionExt, EventMask, Window},
+        },
+        rust_connection::RustConnection,
+    };
+
+    use super::set_workspace;
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            _NET_CURRENT_DESKTOP,
+            _NET_DESKTOP_NAMES,
+            UTF8_STRING,
+        }
+    }
+
+    pub fn watch() -> Result<()> {
+        let (conn, screen) = x11rb::connect(None).context("failed to connect to X11")?;
+        let root = conn.setup().roots[screen].root;
+        let atoms = Atoms::new(&conn)?.reply()?;
+
+        conn.change_window_attributes(
+            root,
+            &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+        conn.flush()?;
+
+        update(&conn, root, &atoms);
+        loop {
+            if let Event::PropertyNotify(event) = conn.wait_for_event()?
+                && (event.atom == atoms._NET_CURRENT_DESKTOP
+                    || event.atom == atoms._NET_DESKTOP_NAMES)
+            {
+                update(&conn, root, &atoms);
+            }
+        }
+    }
+
+    fn update(conn: &RustConnection, root: Window, atoms: &Atoms) {
+        let Some(index) = conn
+            .get_property(
+                false,
+                root,
+                atoms._NET_CURRENT_DESKTOP,
+                AtomEnum::CARDINAL,
+                0,
+                1,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32()?.next())
+        else {
+            return;
+        };
+
+        // _NET_DESKTOP_NAMES is a NUL separated list, one per desktop.
+        let name = conn
+            .get_property(
+                false,
+                root,
+                atoms._NET_DESKTOP_NAMES,
+                atoms.UTF8_STRING,
+                0,
+                u32::MAX / 4,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| {
+                reply
+                    .value
+                    .split(|&b| b == 0)
+                    .nth(index as usize)
+                    .map(|name| String::from_utf8_lossy(name).into_owned())
+            })
+            .filter(|name| !name.is_empty());
+
+        set_workspace(name.unwrap_or_else(|| index.to_string()));
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sway {
+    use anyhow::{Context, Result};
+    use serde::Deserialize;
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixStream,
+        path::Path,
+    };
+
+    use super::set_workspace;
+
+    const MAGIC: &[u8] = b"i3-ipc";
+    const GET_WORKSPACES: u32 = 1;
+    const SUBSCRIBE: u32 = 2;
+    const WORKSPACE_EVENT: u32 = 0x8000_0000;
+
+    #[derive(Deserialize)]
+    struct Workspace {
+        name: String,
+        #[serde(default)]
+        focused: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct WorkspaceEvent {
+        change: String,
+        current: Option<Workspace>,
+    }
+
+    fn send(stream: &mut UnixStream, message_type: u32, payload: &[u8]) -> Result<()> {
+        let mut message = MAGIC.to_vec();
+        message.extend((payload.len() as u32).to_ne_bytes());
+        message.extend(message_type.to_ne_bytes());
+        message.extend(payload);
+        stream.write_all(&message)?;
+        Ok(())
+    }
+
+    fn receive(stream: &mut UnixStream) -> Result<(u32, Vec<u8>)> {
+        let mut header = [0u8; 14];
+        stream.read_exact(&mut header)?;
+        anyhow::ensure!(&header[..6] == MAGIC, "invalid sway IPC reply");
+
+        let len = u32::from_ne_bytes(header[6..10].try_into()?) as usize;
+        let message_type = u32::from_ne_bytes(header[10..14].try_into()?);
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload)?;
+        Ok((message_type, payload))
+    }
+
+    pub fn watch(socket: &Path) -> Result<()> {
+        let mut stream = UnixStream::connect(socket)
+            .with_context(|| format!("failed to connect to sway at {}", socket.display()))?;
+
+        send(&mut stream, GET_WORKSPACES, b"")?;
+        let (_, payload) = receive(&mut stream)?;
+        let workspaces: Vec<Workspace> = serde_json::from_slice(&payload)?;
+        if let Some(focused) = workspaces.into_iter().find(|workspace| workspace.focused) {
+            set_workspace(focused.name);
+        }
+
+        send(&mut stream, SUBSCRIBE, br#"["workspace"]"#)?;
+        receive(&mut stream)?;
+
+        loop {
+            let (message_type, payload) = receive(&mut stream)?;
+            if message_type != WORKSPACE_EVENT {
+                continue;
+            }
+
+            let event: WorkspaceEvent = serde_json::from_slice(&payload)?;
+            if event.change == "focus"
+                && let Some(current) = event.current
+            {
+                set_workspace(current.name);
+            }
+        }
+    }
+}